@@ -0,0 +1,242 @@
+use rand::Rng;
+
+use crate::config::MovementModel;
+use crate::game::{Ball, Direction, GameState, Paddle, PaddleAction, Player, BALL_HALF_SIZE};
+
+// Close enough that the paddle doesn't jitter around its target
+const DEAD_ZONE: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Perfect,
+}
+
+struct Tuning {
+    // Steps between the ball changing course and the computer reacting to it
+    reaction_ticks: u32,
+    // Fastest the computer lets its paddle move per step
+    max_speed: f32,
+    // Furthest the computer's guess of where the ball arrives can be off by
+    aim_error: f32,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Perfect,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Perfect => "Perfect",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .iter()
+            .cloned()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    fn tuning(self) -> Tuning {
+        match self {
+            Difficulty::Easy => Tuning {
+                reaction_ticks: 30,
+                max_speed: 2.0,
+                aim_error: 40.0,
+            },
+            Difficulty::Normal => Tuning {
+                reaction_ticks: 18,
+                max_speed: 3.0,
+                aim_error: 28.0,
+            },
+            Difficulty::Hard => Tuning {
+                reaction_ticks: 8,
+                max_speed: 5.0,
+                aim_error: 14.0,
+            },
+            Difficulty::Perfect => Tuning {
+                reaction_ticks: 0,
+                max_speed: 20.0,
+                aim_error: 0.0,
+            },
+        }
+    }
+}
+
+/// A computer player that moves a paddle by pressing the same keys a person would
+#[derive(Clone, Debug)]
+pub struct CpuPlayer {
+    player: Player,
+    difficulty: Difficulty,
+    // Where the centre of the paddle is headed
+    target: f32,
+    // Which ball it was watching and what that ball was doing last time the computer looked
+    ball: usize,
+    ball_incoming: bool,
+    points_played: u32,
+    reaction: Option<u32>,
+}
+
+impl CpuPlayer {
+    pub fn new(player: Player, difficulty: Difficulty) -> CpuPlayer {
+        CpuPlayer {
+            player,
+            difficulty,
+            target: 0.0,
+            ball: 0,
+            ball_incoming: false,
+            points_played: 0,
+            reaction: None,
+        }
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Decide which keys to press and let go of before the next step
+    pub fn think<R: Rng>(&mut self, game: &GameState, rng: &mut R) -> Vec<PaddleAction> {
+        let tuning = self.difficulty.tuning();
+        if game.is_eliminated(self.player) {
+            return Vec::new();
+        }
+        let edge = game.edge(self.player);
+        let (along, across) = (edge.axis(), 1 - edge.axis());
+
+        // Watch whichever ball gets here first, or the first ball if none are coming
+        let face = self.face(game);
+        let incoming = game
+            .balls
+            .iter()
+            .enumerate()
+            .filter_map(|(index, ball)| arrival_time(ball, face, across).map(|time| (index, time)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        let index = incoming.map_or(0, |(index, _)| index);
+        let ball = &game.balls[index];
+        let ball_incoming = incoming.is_some();
+
+        // Take a moment to react to a ball turning around, a new ball to watch or a serve
+        let points_played = game.score.points_played;
+        if index != self.ball
+            || ball_incoming != self.ball_incoming
+            || points_played != self.points_played
+        {
+            self.ball = index;
+            self.ball_incoming = ball_incoming;
+            self.points_played = points_played;
+            self.reaction = Some(tuning.reaction_ticks);
+        }
+        match self.reaction {
+            Some(0) => {
+                self.reaction = None;
+                self.target = if ball_incoming {
+                    let error = if tuning.aim_error > 0.0 {
+                        rng.gen_range(-tuning.aim_error, tuning.aim_error)
+                    } else {
+                        0.0
+                    };
+                    let half = [game.arena().half_width, game.arena().half_height];
+                    predict_arrival(ball, face, across, half[along]) + error
+                } else {
+                    // Wait in the middle for the ball to come back
+                    0.0
+                };
+            }
+            Some(ticks) => self.reaction = Some(ticks - 1),
+            None => (),
+        }
+
+        let rect = game.paddle_rect(self.player);
+        let distance = self.target - (rect.min[along] + rect.max[along]) / 2.0;
+        let paddle = game.paddle(self.player);
+        let movement = &game.config().paddle_movement;
+        // Some paddles' displacement runs back along their axis
+        let velocity = edge.forward() * paddle.velocity;
+        let moving_towards = velocity * distance > 0.0;
+        // With momentum the paddle slides on a bit after its key is let go
+        let stopping_distance = match movement.model {
+            MovementModel::Momentum => {
+                let speed = velocity.abs();
+                speed * (speed + movement.friction) / (2.0 * movement.friction)
+            }
+            MovementModel::Instant => 0.0,
+        };
+        let direction = if distance.abs() < DEAD_ZONE
+            || moving_towards
+                && (stopping_distance >= distance.abs() || velocity.abs() >= tuning.max_speed)
+        {
+            None
+        } else if distance > 0.0 {
+            Some(Direction::Down)
+        } else {
+            Some(Direction::Up)
+        };
+        // Hold the other key when a power-up has turned the controls around
+        let direction = if game.effects.controls_inverted(self.player) {
+            direction.map(Direction::opposite)
+        } else {
+            direction
+        };
+        hold(paddle, direction)
+    }
+
+    // Where the centre of the ball is when it touches this player's paddle
+    fn face(&self, game: &GameState) -> f32 {
+        let edge = game.edge(self.player);
+        let rect = game.paddle_rest_rect(self.player);
+        let across = 1 - edge.axis();
+        if edge.side() < 0.0 {
+            rect.max[across] + BALL_HALF_SIZE
+        } else {
+            rect.min[across] - BALL_HALF_SIZE
+        }
+    }
+}
+
+// The presses and releases that leave only the key for `direction` held down
+fn hold(paddle: &Paddle, direction: Option<Direction>) -> Vec<PaddleAction> {
+    [Direction::Up, Direction::Down]
+        .iter()
+        .filter_map(|&key| match (paddle.is_held(key), direction == Some(key)) {
+            (true, false) => Some(PaddleAction::Release(key)),
+            (false, true) => Some(PaddleAction::Press(key)),
+            _ => None,
+        })
+        .collect()
+}
+
+// Steps until the ball crosses `face` on the `across` axis, if it's heading that way
+fn arrival_time(ball: &Ball, face: f32, across: usize) -> Option<f32> {
+    let time = (face - ball.position[across]) / ball.velocity[across];
+    if time > 0.0 && time.is_finite() {
+        Some(time)
+    } else {
+        None
+    }
+}
+
+// Follow the ball's path, bouncing off the sides `half_length` either side of the middle,
+// to where it crosses `face` on the `across` axis
+fn predict_arrival(ball: &Ball, face: f32, across: usize, half_length: f32) -> f32 {
+    let along = 1 - across;
+    let time = (face - ball.position[across]) / ball.velocity[across];
+    let y = ball.position[along] + ball.velocity[along] * time;
+    // Unfold the bounces, the path repeats every trip down and back up the court
+    let reach = half_length - BALL_HALF_SIZE;
+    let y = (y + reach).rem_euclid(4.0 * reach);
+    if y < 2.0 * reach {
+        y - reach
+    } else {
+        3.0 * reach - y
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use std::f32::consts::PI;
+use std::fs;
+use std::path::Path;
+
+use crate::collision::{self, Rect};
+use crate::game::BALL_HALF_SIZE;
+
+/// The biggest court there can be, in world units either side of the middle. The camera shows this
+/// much and the default arena fills it.
+pub const MAX_HALF_SIZE: [f32; 2] = [100.0, 100.0];
+
+/// The shape of the court, anything missing from an arena file is left at its default
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Arena {
+    // Distance from the middle to each goal line
+    pub half_width: f32,
+    // Distance from the middle to the top and bottom walls
+    pub half_height: f32,
+    // Height of the gap in the middle of each end the ball has to get through to score,
+    // the rest of the end is walled off
+    pub goal_height: f32,
+    pub net: bool,
+    pub obstacles: Vec<Obstacle>,
+}
+
+impl Default for Arena {
+    fn default() -> Arena {
+        Arena {
+            half_width: MAX_HALF_SIZE[0],
+            half_height: MAX_HALF_SIZE[1],
+            goal_height: 2.0 * MAX_HALF_SIZE[1],
+            net: true,
+            obstacles: Vec::new(),
+        }
+    }
+}
+
+/// A block on the court the ball bounces off
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Obstacle {
+    pub rect: Rect,
+    // How far the block slides from where it starts and back again, nowhere for one that stays put
+    pub travel: [f32; 2],
+    // Seconds for the block to slide there and back
+    pub period: f32,
+}
+
+impl Default for Obstacle {
+    fn default() -> Obstacle {
+        Obstacle {
+            rect: Rect {
+                min: [0.0; 2],
+                max: [0.0; 2],
+            },
+            travel: [0.0; 2],
+            period: 0.0,
+        }
+    }
+}
+
+impl Obstacle {
+    /// Where the block is `time` seconds into a match
+    pub fn rect_at(&self, time: f32) -> Rect {
+        if self.period <= 0.0 {
+            return self.rect;
+        }
+        // Ease in and out of each end of the slide
+        let along = (1.0 - (2.0 * PI * time / self.period).cos()) / 2.0;
+        let offset = [self.travel[0] * along, self.travel[1] * along];
+        Rect {
+            min: [self.rect.min[0] + offset[0], self.rect.min[1] + offset[1]],
+            max: [self.rect.max[0] + offset[0], self.rect.max[1] + offset[1]],
+        }
+    }
+}
+
+impl Arena {
+    /// Read an arena from a RON file
+    pub fn load(path: &Path) -> Result<Arena, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        ron::de::from_str(&text).map_err(|err| format!("bad arena {}: {}", path.display(), err))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.half_width < 30.0 || self.half_width > MAX_HALF_SIZE[0] {
+            return Err(format!(
+                "arena half width must be from 30 to {}",
+                MAX_HALF_SIZE[0]
+            ));
+        }
+        if self.half_height < 30.0 || self.half_height > MAX_HALF_SIZE[1] {
+            return Err(format!(
+                "arena half height must be from 30 to {}",
+                MAX_HALF_SIZE[1]
+            ));
+        }
+        if self.goal_height <= 0.0 {
+            return Err("arena goals must be more than 0 high".to_string());
+        }
+        for obstacle in &self.obstacles {
+            let rect = &obstacle.rect;
+            if rect.min[0] >= rect.max[0] || rect.min[1] >= rect.max[1] {
+                return Err("arena obstacles need their min less than their max".to_string());
+            }
+            // The ball is served from the middle, so no obstacle can be there or slide across it.
+            // Sliding the ball the other way against the obstacle where it starts is the same.
+            let back = [-obstacle.travel[0], -obstacle.travel[1]];
+            if collision::overlaps([0.0, 0.0], BALL_HALF_SIZE, rect)
+                || collision::sweep([0.0, 0.0], back, BALL_HALF_SIZE, rect).is_some()
+            {
+                return Err("arena obstacles can't cover the middle of the court".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arena_with(obstacle: Obstacle) -> Arena {
+        Arena {
+            obstacles: vec![obstacle],
+            ..Arena::default()
+        }
+    }
+
+    #[test]
+    fn obstacles_have_to_keep_clear_of_the_middle() {
+        let rect = Rect {
+            min: [-10.0, 20.0],
+            max: [10.0, 30.0],
+        };
+        let beside = Obstacle {
+            rect,
+            ..Obstacle::default()
+        };
+        assert!(arena_with(beside.clone()).validate().is_ok());
+
+        let over = Obstacle {
+            rect: Rect {
+                min: [-10.0, -5.0],
+                max: [10.0, 5.0],
+            },
+            ..Obstacle::default()
+        };
+        assert!(arena_with(over).validate().is_err());
+
+        let sliding_across = Obstacle {
+            travel: [0.0, -60.0],
+            period: 4.0,
+            ..beside.clone()
+        };
+        assert!(arena_with(sliding_across).validate().is_err());
+
+        let sliding_past = Obstacle {
+            travel: [50.0, 0.0],
+            period: 4.0,
+            ..beside
+        };
+        assert!(arena_with(sliding_past).validate().is_ok());
+    }
+}
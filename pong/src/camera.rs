@@ -0,0 +1,92 @@
+use crate::arena::MAX_HALF_SIZE;
+
+/// The part of the world drawn on screen, in world units
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub centre: [f32; 2],
+    // How far the view reaches either side of the centre
+    pub half_size: [f32; 2],
+}
+
+impl Default for Camera {
+    // The biggest court there can be, filling the screen
+    fn default() -> Camera {
+        Camera {
+            centre: [0.0, 0.0],
+            half_size: MAX_HALF_SIZE,
+        }
+    }
+}
+
+impl Camera {
+    /// Scale then offset taking world units to normalized device coordinates, as the shaders take it
+    pub fn transform(&self) -> [f32; 4] {
+        let scale = [1.0 / self.half_size[0], 1.0 / self.half_size[1]];
+        [
+            scale[0],
+            scale[1],
+            -self.centre[0] * scale[0],
+            -self.centre[1] * scale[1],
+        ]
+    }
+
+    /// Where the view goes in a window `window` pixels across, as big as it fits without
+    /// stretching, leaving bars down the sides or along the top and bottom. Gives the top left
+    /// corner and the size, in pixels.
+    pub fn letterbox(&self, window: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+        let aspect = self.half_size[0] / self.half_size[1];
+        let size = if window[0] > window[1] * aspect {
+            [window[1] * aspect, window[1]]
+        } else {
+            [window[0], window[0] / aspect]
+        };
+        let corner = [(window[0] - size[0]) / 2.0, (window[1] - size[1]) / 2.0];
+        (corner, size)
+    }
+
+    /// Like `transform`, shrunk to fit the letterbox in a window `window` pixels across. Every
+    /// vertex shader takes this as its `projection` push constant, scaling by `xy` then offsetting
+    /// by `zw`, so the court keeps its proportions whatever shape the window is.
+    pub fn projection(&self, window: [f32; 2]) -> [f32; 4] {
+        let (_, size) = self.letterbox(window);
+        let fit = [size[0] / window[0], size[1] / window[1]];
+        let transform = self.transform();
+        [
+            transform[0] * fit[0],
+            transform[1] * fit[1],
+            transform[2] * fit[0],
+            transform[3] * fit[1],
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_court_fills_the_letterbox() {
+        let camera = Camera::default();
+        // Wide, tall and just the right shape
+        let windows = [
+            ([800.0, 400.0], [200.0, 0.0], [400.0, 400.0]),
+            ([400.0, 800.0], [0.0, 200.0], [400.0, 400.0]),
+            ([600.0, 600.0], [0.0, 0.0], [600.0, 600.0]),
+        ];
+        for &(window, corner, size) in windows.iter() {
+            assert_eq!(camera.letterbox(window), (corner, size));
+            // Corners of the court land on the corners of the letterbox, in pixels
+            let projection = camera.projection(window);
+            for axis in 0..2 {
+                let pixel = |world: f32| {
+                    let ndc = world * projection[axis] + projection[2 + axis];
+                    (ndc + 1.0) / 2.0 * window[axis]
+                };
+                let low = camera.centre[axis] - camera.half_size[axis];
+                let high = camera.centre[axis] + camera.half_size[axis];
+                assert!((pixel(low) - corner[axis]).abs() < 1e-3);
+                assert!((pixel(high) - (corner[axis] + size[axis])).abs() < 1e-3);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// Axis aligned rectangle
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rect {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+/// Where a moving box first touched a rectangle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    // Fraction of the movement made before touching, from 0 to 1
+    pub time: f32,
+    // Centre of the moving box at the moment of contact
+    pub position: [f32; 2],
+    // Point on the rectangle's surface that was touched
+    pub point: [f32; 2],
+    // Outward facing normal of the surface that was touched
+    pub normal: [f32; 2],
+}
+
+/// Whether a box of `half_size` at `centre` is overlapping `rect`
+pub fn overlaps(centre: [f32; 2], half_size: f32, rect: &Rect) -> bool {
+    (0..2).all(|axis| {
+        centre[axis] + half_size > rect.min[axis] && centre[axis] - half_size < rect.max[axis]
+    })
+}
+
+/// Sweep a box of `half_size` from `start` along `delta` against `rect`.
+/// Only surfaces the box is moving into count, so a box touching or inside the rectangle can leave it.
+pub fn sweep(start: [f32; 2], delta: [f32; 2], half_size: f32, rect: &Rect) -> Option<Contact> {
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = [0.0; 2];
+    for axis in 0..2 {
+        // Grow the rectangle by the box so the box can be treated as a point
+        let min = rect.min[axis] - half_size;
+        let max = rect.max[axis] + half_size;
+        if delta[axis] == 0.0 {
+            if start[axis] <= min || start[axis] >= max {
+                return None;
+            }
+            continue;
+        }
+        let (near, far, side) = if delta[axis] > 0.0 {
+            (min, max, -1.0)
+        } else {
+            (max, min, 1.0)
+        };
+        let near = (near - start[axis]) / delta[axis];
+        let far = (far - start[axis]) / delta[axis];
+        if near > entry {
+            entry = near;
+            normal = [0.0; 2];
+            normal[axis] = side;
+        }
+        exit = exit.min(far);
+    }
+    if entry > exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+    let position = [start[0] + delta[0] * entry, start[1] + delta[1] * entry];
+    Some(Contact {
+        time: entry,
+        position,
+        point: [
+            position[0] - normal[0] * half_size,
+            position[1] - normal[1] * half_size,
+        ],
+        normal,
+    })
+}
+
+/// Bounce a velocity off a surface with the given normal
+pub fn reflect(velocity: [f32; 2], normal: [f32; 2]) -> [f32; 2] {
+    let dot = velocity[0] * normal[0] + velocity[1] * normal[1];
+    [
+        velocity[0] - 2.0 * dot * normal[0],
+        velocity[1] - 2.0 * dot * normal[1],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALL: Rect = Rect {
+        min: [10.0, -20.0],
+        max: [12.0, 20.0],
+    };
+
+    #[test]
+    fn fast_box_hits_a_thin_wall_instead_of_tunnelling() {
+        // Far enough in one step to jump right over the wall
+        let contact = sweep([0.0, 0.0], [100.0, 0.0], 1.0, &WALL).unwrap();
+        assert_eq!(contact.time, 0.09);
+        assert_eq!(contact.position, [9.0, 0.0]);
+        assert_eq!(contact.normal, [-1.0, 0.0]);
+    }
+
+    #[test]
+    fn normal_faces_the_side_hit_first() {
+        // Coming down at an angle onto the top of the wall
+        let contact = sweep([11.0, -30.0], [2.0, 20.0], 1.0, &WALL).unwrap();
+        assert_eq!(contact.normal, [0.0, -1.0]);
+        assert_eq!(contact.time, 0.45);
+        assert_eq!(contact.position, [11.9, -21.0]);
+    }
+
+    #[test]
+    fn misses_and_moving_away_dont_count() {
+        // Falls short
+        assert_eq!(sweep([0.0, 0.0], [5.0, 0.0], 1.0, &WALL), None);
+        // Passes above
+        assert_eq!(sweep([0.0, -30.0], [100.0, 0.0], 1.0, &WALL), None);
+        // Touching and leaving
+        assert_eq!(sweep([9.0, 0.0], [-5.0, 0.0], 1.0, &WALL), None);
+    }
+
+    #[test]
+    fn reflect_flips_the_velocity_along_the_normal() {
+        assert_eq!(reflect([3.0, 4.0], [-1.0, 0.0]), [-3.0, 4.0]);
+        assert_eq!(reflect([3.0, 4.0], [0.0, -1.0]), [3.0, -4.0]);
+    }
+}
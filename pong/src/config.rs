@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+
+use crate::arena::Arena;
+use crate::level::Level;
+use crate::rules::MatchRules;
+
+/// Tuning for how the game plays, anything missing from a config file is left at its default
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    // Steepest angle in degrees the ball leaves a paddle at, when it hits the very end of it
+    pub max_bounce_angle: f32,
+    // Distance the ball moves per step when served
+    pub ball_speed: f32,
+    // Added to the ball's speed every time a paddle hits it
+    pub ball_speed_increase: f32,
+    // The ball never speeds up past this
+    pub max_ball_speed: f32,
+    pub paddle_movement: PaddleMovement,
+    pub rules: MatchRules,
+    pub multiball: Multiball,
+    pub power_ups: PowerUps,
+    pub spin: Spin,
+    pub arena: Arena,
+    // Bricks for the bricks mode
+    pub level: Level,
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            max_bounce_angle: 60.0,
+            ball_speed: 1.5,
+            ball_speed_increase: 0.1,
+            max_ball_speed: 4.0,
+            paddle_movement: PaddleMovement::default(),
+            rules: MatchRules::default(),
+            multiball: Multiball::default(),
+            power_ups: PowerUps::default(),
+            spin: Spin::default(),
+            arena: Arena::default(),
+            level: Level::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementModel {
+    // Paddles speed up while a key is held and slide to a stop once it's let go
+    Momentum,
+    // Paddles move at full speed while a key is held and stop dead when it's let go
+    Instant,
+}
+
+/// How paddles move while their keys are held down
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaddleMovement {
+    pub model: MovementModel,
+    // Fastest a paddle moves, distance per step
+    pub max_speed: f32,
+    // Speed gained each step a key is held, with momentum
+    pub acceleration: f32,
+    // Speed lost each step no key is held, with momentum
+    pub friction: f32,
+}
+
+impl Default for PaddleMovement {
+    fn default() -> PaddleMovement {
+        PaddleMovement {
+            model: MovementModel::Momentum,
+            max_speed: 5.0,
+            acceleration: 1.0,
+            friction: 1.0,
+        }
+    }
+}
+
+impl PaddleMovement {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_speed <= 0.0 {
+            return Err("paddle max speed must be more than 0".to_string());
+        }
+        if self.model == MovementModel::Momentum
+            && (self.acceleration <= 0.0 || self.friction <= 0.0)
+        {
+            return Err("paddle acceleration and friction must be more than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// When extra balls join a rally, only once `max_balls` is more than 1
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Multiball {
+    // Most balls in play at once
+    pub max_balls: u32,
+    // Seconds of rally before another ball is added, 0 to never add one on a timer
+    pub spawn_delay: f32,
+    // Paddle hits before another ball is added, 0 to never add one for hits
+    pub spawn_hits: u32,
+}
+
+impl Default for Multiball {
+    fn default() -> Multiball {
+        Multiball {
+            max_balls: 1,
+            spawn_delay: 10.0,
+            spawn_hits: 6,
+        }
+    }
+}
+
+impl Multiball {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_balls == 0 {
+            return Err("there must be at least 1 ball".to_string());
+        }
+        if self.spawn_delay < 0.0 {
+            return Err("ball spawn delay can't be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl GameConfig {
+    /// Read a config from a RON file
+    pub fn load(path: &Path) -> Result<GameConfig, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        ron::de::from_str(&text).map_err(|err| format!("bad config {}: {}", path.display(), err))
+    }
+}
+
+/// Pickups that appear on the court during a rally and do something to whoever sends a ball through them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerUps {
+    pub enabled: bool,
+    // Seconds of rally between pickups appearing
+    pub spawn_delay: f32,
+    // Seconds an effect lasts once it's picked up
+    pub duration: f32,
+    // Most pickups waiting on the court at once
+    pub max_on_court: u32,
+}
+
+impl Default for PowerUps {
+    fn default() -> PowerUps {
+        PowerUps {
+            enabled: false,
+            spawn_delay: 8.0,
+            duration: 10.0,
+            max_on_court: 2,
+        }
+    }
+}
+
+impl PowerUps {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.spawn_delay <= 0.0 {
+            return Err("power-up spawn delay must be more than 0".to_string());
+        }
+        if self.duration < 0.0 {
+            return Err("power-up duration can't be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Paddles moving as they hit the ball set it spinning, which bends its path
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Spin {
+    pub enabled: bool,
+    // Spin given to the ball, in radians per step, for each unit of paddle speed when it's hit
+    pub transfer: f32,
+    // How far the ball's path turns each step for each radian per step it's spinning
+    pub curve: f32,
+    // Fraction of its spin the ball keeps from one step to the next
+    pub decay: f32,
+    // Fraction of its spin the ball loses each bounce, pushing it sideways along whatever it hit
+    pub grip: f32,
+}
+
+impl Default for Spin {
+    fn default() -> Spin {
+        Spin {
+            enabled: false,
+            transfer: 0.02,
+            curve: 0.06,
+            decay: 0.99,
+            grip: 0.5,
+        }
+    }
+}
+
+impl Spin {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.transfer < 0.0 || self.curve < 0.0 {
+            return Err("spin transfer and curve can't be negative".to_string());
+        }
+        if self.decay < 0.0 || self.decay > 1.0 || self.grip < 0.0 || self.grip > 1.0 {
+            return Err("spin decay and grip must be from 0 to 1".to_string());
+        }
+        Ok(())
+    }
+}
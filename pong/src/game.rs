@@ -0,0 +1,1496 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::arena::Arena;
+use crate::collision::{self, Contact, Rect};
+use crate::config::{GameConfig, MovementModel, PaddleMovement, Spin};
+use crate::level::Brick;
+use crate::powerup::{Effects, PowerUp, PowerUpKind, POWER_UP_HALF_SIZE};
+use crate::rules::{MatchScore, Outcome, ServeTo};
+use crate::stats::MatchStats;
+use crate::timestep::TICKS_PER_SECOND;
+
+pub const MAX_PLAYERS: usize = 4;
+// Positions are in world units, with +y down and the middle of the court at 0. The camera maps them
+// to the screen.
+pub const BALL_HALF_SIZE: f32 = 3.0;
+// How far in from its goal line each side of a paddle is
+pub const PADDLE_INSET: [f32; 2] = [10.0, 20.0];
+// Where each side of a forward paddle in doubles is, as a fraction of the way from the goal line to the middle
+pub const FORWARD_PADDLE_INSET: [f32; 2] = [0.4, 0.5];
+// Length of a paddle with no power-ups working on it
+pub const PADDLE_HEIGHT: f32 = 50.0;
+// With more than two players the corners are blocked off so paddles can't run into each other
+pub const CORNER_SIZE: f32 = 20.0;
+// A ball can't bounce more often than this in a single step
+const MAX_BOUNCES: usize = 4;
+// Steepest angle in degrees the ball can be served at
+const MAX_SERVE_ANGLE: f32 = 45.0;
+// How fast power-ups dropped by bricks fall towards the paddle
+const DROP_SPEED: f32 = 0.8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Player {
+    One,
+    Two,
+    Three,
+    Four,
+}
+
+impl Player {
+    pub const ALL: [Player; MAX_PLAYERS] = [Player::One, Player::Two, Player::Three, Player::Four];
+
+    pub fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+            Player::Three => 2,
+            Player::Four => 3,
+        }
+    }
+
+    /// The number shown on screen, starting from 1
+    pub fn number(self) -> usize {
+        self.index() + 1
+    }
+
+    /// The player across the court
+    pub fn other(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+            Player::Three => Player::Four,
+            Player::Four => Player::Three,
+        }
+    }
+}
+
+/// How the players are placed around the court
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layout {
+    // Player 1 on their own at the left, hitting against a wall at the right
+    Solo,
+    // Player 1 on their own at the left, knocking down bricks with a wall behind them
+    Bricks,
+    // One player at each end
+    Singles,
+    // Two teams, players 1 and 2 at the back of the left and right ends and 3 and 4 in front of them
+    Doubles,
+    // Players 1 to 4 on the left, right, top and bottom
+    FourWay,
+}
+
+impl Layout {
+    pub fn players(self) -> usize {
+        match self {
+            Layout::Solo | Layout::Bricks => 1,
+            Layout::Singles => 2,
+            Layout::Doubles | Layout::FourWay => 4,
+        }
+    }
+
+    /// The side of the court a player guards
+    pub fn edge(self, player: Player) -> Edge {
+        match (self, player) {
+            (_, Player::One) | (Layout::Doubles, Player::Three) => Edge::Left,
+            (_, Player::Two) | (Layout::Doubles, Player::Four) => Edge::Right,
+            (_, Player::Three) => Edge::Top,
+            (_, Player::Four) => Edge::Bottom,
+        }
+    }
+
+    /// Whether a player's paddle sits forward of their partner's
+    pub fn is_forward(self, player: Player) -> bool {
+        self == Layout::Doubles && matches!(player, Player::Three | Player::Four)
+    }
+
+    /// Players keep their own lives rather than scoring points off the other end
+    pub fn has_lives(self) -> bool {
+        self == Layout::FourWay
+    }
+
+    /// Whether there's somebody at both the left and the right to score points off each other
+    pub fn has_two_ends(self) -> bool {
+        matches!(self, Layout::Singles | Layout::Doubles)
+    }
+
+    /// Room left in the corners of the court, where nobody's paddle reaches
+    pub fn corner_size(self) -> f32 {
+        if self == Layout::FourWay {
+            CORNER_SIZE
+        } else {
+            0.0
+        }
+    }
+
+    /// Check a match can be played this way, with the paddles fitting along their edges of the
+    /// arena and the level fitting in it for bricks
+    pub fn validate(self, config: &GameConfig) -> Result<(), String> {
+        let arena = &config.arena;
+        let half = [arena.half_width, arena.half_height];
+        let fits = Player::ALL[..self.players()].iter().all(|&player| {
+            let along = half[self.edge(player).axis()];
+            2.0 * (along - self.corner_size()) >= PADDLE_HEIGHT
+        });
+        if !fits {
+            return Err("the arena is too small for the paddles in this mode".to_string());
+        }
+        if self == Layout::Bricks {
+            config.level.validate(arena)?;
+        }
+        Ok(())
+    }
+
+    /// What a player, or their team, is called on screen
+    pub fn name(self, player: Player) -> String {
+        match (self, self.edge(player)) {
+            (Layout::Doubles, Edge::Left) => "Left Team".to_string(),
+            (Layout::Doubles, _) => "Right Team".to_string(),
+            _ => format!("Player {}", player.number()),
+        }
+    }
+}
+
+/// A side of the court, where a paddle and its goal go
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom];
+
+    /// The axis a paddle on this edge slides along, 1 for up and down
+    pub fn axis(self) -> usize {
+        match self {
+            Edge::Left | Edge::Right => 1,
+            Edge::Top | Edge::Bottom => 0,
+        }
+    }
+
+    /// Which end of the other axis the edge is at, -1 for the left and top
+    pub fn side(self) -> f32 {
+        match self {
+            Edge::Left | Edge::Top => -1.0,
+            Edge::Right | Edge::Bottom => 1.0,
+        }
+    }
+
+    /// Which way along its axis displacement moves a paddle on this edge,
+    /// paddles start at the end nearest `side` and move away from it
+    pub fn forward(self) -> f32 {
+        -self.side()
+    }
+}
+
+/// Which way along its edge a paddle is pushed, for paddles on the top and bottom Up is left and Down is right
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+}
+
+/// A key going down or up for one of a player's paddle controls
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaddleAction {
+    Press(Direction),
+    Release(Direction),
+}
+
+/// Everything the players did since the last step
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Inputs {
+    pub players: [Vec<PaddleAction>; MAX_PLAYERS],
+    // Serve now rather than waiting for the countdown
+    pub serve: bool,
+}
+
+impl Inputs {
+    pub fn push(&mut self, player: Player, action: PaddleAction) {
+        self.player_mut(player).push(action);
+    }
+
+    pub fn player_mut(&mut self, player: Player) -> &mut Vec<PaddleAction> {
+        &mut self.players[player.index()]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.iter().all(|actions| actions.is_empty()) && !self.serve
+    }
+
+    pub fn clear(&mut self) {
+        for actions in self.players.iter_mut() {
+            actions.clear();
+        }
+        self.serve = false;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    Served(Player),
+    // Another ball joined the rally
+    BallAdded,
+    // A player sent a ball through a power-up
+    PowerUpCollected(Player, PowerUpKind),
+    PaddleHit(Player),
+    WallBounce,
+    PointScored(Player),
+    // With more than two players, a ball got past someone
+    LifeLost,
+    // This player is out of lives, their edge is walled off
+    Eliminated(Player),
+    // Time ran out with nobody ahead, whoever gets ahead next wins
+    SuddenDeath,
+    // The ball got past the player practicing, after this many returns in a row
+    StreakEnded(u32),
+    BrickHit,
+    // A brick took its last hit
+    BrickBroken,
+    // Playing on your own, and out of lives
+    MatchLost,
+    SetWon(Player),
+    MatchWon(Player),
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Paddle {
+    pub displacement: f32,
+    // Distance moved per step
+    pub velocity: f32,
+    previous_displacement: f32,
+    // The player's keys that are down at the moment, key repeats don't change them
+    held_up: bool,
+    held_down: bool,
+}
+
+impl Paddle {
+    /// Displacement for drawing, `alpha` of the way from the previous step to this one
+    pub fn interpolate(&self, alpha: f32) -> f32 {
+        lerp(self.previous_displacement, self.displacement, alpha)
+    }
+
+    pub fn is_held(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::Up => self.held_up,
+            Direction::Down => self.held_down,
+        }
+    }
+
+    fn apply(&mut self, action: PaddleAction) {
+        match action {
+            PaddleAction::Press(Direction::Up) => self.held_up = true,
+            PaddleAction::Press(Direction::Down) => self.held_down = true,
+            PaddleAction::Release(Direction::Up) => self.held_up = false,
+            PaddleAction::Release(Direction::Down) => self.held_down = false,
+        }
+    }
+
+    // Move for one step with whichever keys are held, up to `travel` from where it starts
+    fn update(&mut self, edge: Edge, travel: f32, movement: &PaddleMovement, inverted: bool) {
+        // Holding both keys is the same as holding neither
+        let pushed = match (self.held_up, self.held_down) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+        // Paddles on the right and bottom are displaced back along their axis
+        let push = if inverted { -pushed } else { pushed } * edge.forward();
+        let max_speed = movement.max_speed;
+        self.velocity = match movement.model {
+            MovementModel::Instant => push * max_speed,
+            MovementModel::Momentum if push != 0.0 => {
+                (self.velocity + push * movement.acceleration).clamp(-max_speed, max_speed)
+            }
+            // Friction slows the paddle down without turning it around
+            MovementModel::Momentum => {
+                (self.velocity.abs() - movement.friction).max(0.0) * self.velocity.signum()
+            }
+        };
+        self.displacement += self.velocity;
+        if self.displacement < 0.0 || self.displacement > travel {
+            self.displacement = self.displacement.clamp(0.0, travel);
+            self.velocity = 0.0;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ball {
+    pub position: [f32; 2],
+    // Distance moved per step
+    pub velocity: [f32; 2],
+    previous_position: [f32; 2],
+    // Whoever hit the ball last, nobody has when it's just been served
+    pub last_hit: Option<Player>,
+    // Radians the ball turns each step, clockwise on screen
+    pub spin: f32,
+    angle: f32,
+    previous_angle: f32,
+}
+
+impl Ball {
+    fn new(position: [f32; 2]) -> Ball {
+        Ball {
+            position,
+            velocity: [0.0; 2],
+            previous_position: position,
+            last_hit: None,
+            spin: 0.0,
+            angle: 0.0,
+            previous_angle: 0.0,
+        }
+    }
+
+    /// Position for drawing, `alpha` of the way from the previous step to this one
+    pub fn interpolate(&self, alpha: f32) -> [f32; 2] {
+        [
+            lerp(self.previous_position[0], self.position[0], alpha),
+            lerp(self.previous_position[1], self.position[1], alpha),
+        ]
+    }
+
+    /// How far round the ball has turned for drawing, `alpha` of the way from the previous step to this one
+    pub fn interpolate_angle(&self, alpha: f32) -> f32 {
+        lerp(self.previous_angle, self.angle, alpha)
+    }
+
+    // Spinning bends the ball's path the same way it turns, but not so far that it heads more than
+    // `max_angle` degrees off the `across` axis, then the spin wears off a little
+    fn curve(&mut self, spin: &Spin, across: usize, max_angle: f32) {
+        self.angle += self.spin;
+        let turned = rotate(self.velocity, self.spin * spin.curve);
+        let off_across = |velocity: [f32; 2]| {
+            velocity[1 - across]
+                .abs()
+                .atan2(velocity[across].abs())
+                .to_degrees()
+        };
+        let still_across = turned[across] * self.velocity[across] > 0.0;
+        if still_across
+            && (off_across(turned) <= max_angle || off_across(turned) < off_across(self.velocity))
+        {
+            self.velocity = turned;
+        }
+        self.spin *= spin.decay;
+    }
+
+    // The spinning ball grips whatever it bounced off, which pushes it sideways and takes some spin away
+    fn grip(&mut self, spin: &Spin, normal: [f32; 2]) {
+        let kick = spin.grip * self.spin * BALL_HALF_SIZE;
+        let before = speed(self.velocity);
+        let kicked = [
+            self.velocity[0] - kick * normal[1],
+            self.velocity[1] + kick * normal[0],
+        ];
+        // Only its direction changes, not how fast it's going
+        let scale = before / speed(kicked);
+        self.velocity = [kicked[0] * scale, kicked[1] * scale];
+        self.spin *= 1.0 - spin.grip;
+    }
+}
+
+fn lerp(from: f32, to: f32, alpha: f32) -> f32 {
+    from + (to - from) * alpha
+}
+
+/// The whole game of pong without a window, stepped at a fixed rate
+#[derive(Clone, Debug)]
+pub struct GameState {
+    // The first ball is the one that gets served, more join it in multiball
+    pub balls: Vec<Ball>,
+    // One for each player, in order
+    pub paddles: Vec<Paddle>,
+    pub power_ups: Vec<PowerUp>,
+    // What's left of the level in the bricks mode
+    pub bricks: Vec<Brick>,
+    pub effects: Effects,
+    pub score: MatchScore,
+    pub stats: MatchStats,
+    pub winner: Option<Player>,
+    // The match has finished, whether anybody won it or not
+    over: bool,
+    // Steps left before the ball waiting in the middle gets served
+    pub serve_countdown: Option<u32>,
+    last_conceder: Option<Player>,
+    last_receiver: Option<Player>,
+    // Steps and paddle hits since a ball last joined the rally
+    rally_ticks: u32,
+    rally_hits: u32,
+    // Steps of rally since a power-up last appeared
+    power_up_ticks: u32,
+    // Steps since the match started, moving obstacles follow this
+    ticks: u32,
+    // Steps left in a timed match
+    clock: Option<u32>,
+    layout: Layout,
+    config: GameConfig,
+    // Everything random in the game comes from here so the same seed plays out the same way
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameState {
+    pub fn new(config: GameConfig, layout: Layout, seed: u64) -> GameState {
+        let clock = if config.rules.is_timed() {
+            let ticks = config.rules.time_limit * TICKS_PER_SECOND as f32;
+            Some((ticks as u32).max(1))
+        } else {
+            None
+        };
+        let mut game = GameState {
+            balls: Vec::new(),
+            paddles: vec![Paddle::default(); layout.players()],
+            power_ups: Vec::new(),
+            bricks: if layout == Layout::Bricks {
+                config.level.bricks()
+            } else {
+                Vec::new()
+            },
+            effects: Effects::default(),
+            score: MatchScore::default(),
+            stats: MatchStats::new(layout),
+            winner: None,
+            over: false,
+            serve_countdown: None,
+            last_conceder: None,
+            last_receiver: None,
+            rally_ticks: 0,
+            rally_hits: 0,
+            power_up_ticks: 0,
+            ticks: 0,
+            clock,
+            layout,
+            config,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        game.wait_to_serve();
+        game
+    }
+
+    pub fn paddle(&self, player: Player) -> &Paddle {
+        &self.paddles[player.index()]
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn arena(&self) -> &Arena {
+        &self.config.arena
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn edge(&self, player: Player) -> Edge {
+        self.layout.edge(player)
+    }
+
+    /// Seconds left in a timed match
+    pub fn time_left(&self) -> Option<f32> {
+        self.clock
+            .map(|ticks| ticks as f32 / TICKS_PER_SECOND as f32)
+    }
+
+    pub fn is_sudden_death(&self) -> bool {
+        self.clock == Some(0) && !self.over
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// Everyone in the match, whether they're still in it or not
+    pub fn players(&self) -> &'static [Player] {
+        &Player::ALL[..self.paddles.len()]
+    }
+
+    /// Everyone who hasn't been knocked out
+    pub fn active_players(&self) -> Vec<Player> {
+        self.players()
+            .iter()
+            .cloned()
+            .filter(|&player| !self.is_eliminated(player))
+            .collect()
+    }
+
+    /// Only matches where everyone has lives knock people out
+    pub fn is_eliminated(&self, player: Player) -> bool {
+        self.layout.has_lives() && self.score.lives_left(player, &self.config.rules) == 0
+    }
+
+    /// The player guarding an edge, if anyone is. In doubles it's the one at the back,
+    /// who the team's score is kept under.
+    pub fn player_at(&self, edge: Edge) -> Option<Player> {
+        self.players()
+            .iter()
+            .cloned()
+            .find(|&player| self.edge(player) == edge && !self.is_eliminated(player))
+    }
+
+    // Half the court's size along each axis
+    fn half_size(&self) -> [f32; 2] {
+        [self.arena().half_width, self.arena().half_height]
+    }
+
+    /// Furthest a paddle can be displaced from where it starts, at its current size
+    pub fn paddle_travel(&self, player: Player) -> f32 {
+        let along = self.half_size()[self.edge(player).axis()];
+        (2.0 * (along - self.layout.corner_size()) - self.paddle_length(player)).max(0.0)
+    }
+
+    // How long a paddle is along its edge, grown or shrunk by any power-ups
+    fn paddle_length(&self, player: Player) -> f32 {
+        PADDLE_HEIGHT * self.effects.paddle_scale(player)
+    }
+
+    /// How far a paddle has moved from where it starts, `alpha` of the way from the previous step to this one
+    pub fn paddle_offset(&self, player: Player, alpha: f32) -> [f32; 2] {
+        let edge = self.edge(player);
+        let mut offset = [0.0; 2];
+        offset[edge.axis()] = edge.forward() * self.paddle(player).interpolate(alpha);
+        offset
+    }
+
+    /// The area of the court a paddle blocks
+    pub fn paddle_rect(&self, player: Player) -> Rect {
+        let mut rect = self.paddle_rest_rect(player);
+        let offset = self.paddle_offset(player, 1.0);
+        for (axis, offset) in offset.iter().enumerate() {
+            rect.min[axis] += offset;
+            rect.max[axis] += offset;
+        }
+        rect
+    }
+
+    /// Where a paddle would be with no displacement, at its current size
+    pub fn paddle_rest_rect(&self, player: Player) -> Rect {
+        let edge = self.edge(player);
+        let (along, across) = (edge.axis(), 1 - edge.axis());
+        let half = self.half_size();
+        let side = edge.side();
+        let half_length = self.paddle_length(player) / 2.0;
+        let centre = side * (half[along] - self.layout.corner_size() - half_length);
+        let inset = if self.layout.is_forward(player) {
+            [
+                FORWARD_PADDLE_INSET[0] * half[across],
+                FORWARD_PADDLE_INSET[1] * half[across],
+            ]
+        } else {
+            PADDLE_INSET
+        };
+        let face = [
+            side * (half[across] - inset[0]),
+            side * (half[across] - inset[1]),
+        ];
+        let mut rect = Rect {
+            min: [0.0; 2],
+            max: [0.0; 2],
+        };
+        rect.min[along] = centre - half_length;
+        rect.max[along] = centre + half_length;
+        rect.min[across] = face[0].min(face[1]);
+        rect.max[across] = face[0].max(face[1]);
+        rect
+    }
+
+    /// Walls along every edge nobody is guarding, either side of the goals and in the corners
+    pub fn walls(&self) -> Vec<Rect> {
+        let half = self.half_size();
+        let mut walls = Vec::new();
+        for &edge in Edge::ALL.iter() {
+            let (along, across) = (edge.axis(), 1 - edge.axis());
+            let side = edge.side();
+            let goal = match self.player_at(edge) {
+                Some(_) => self.arena().goal_height / 2.0,
+                None => 0.0,
+            };
+            if goal >= half[along] {
+                continue;
+            }
+            // Blocks from the edge of the court outwards, running past the corners
+            let outside = [side * half[across], side * 2.0 * half[across]];
+            let spans = if goal > 0.0 {
+                vec![[-2.0 * half[along], -goal], [goal, 2.0 * half[along]]]
+            } else {
+                vec![[-2.0 * half[along], 2.0 * half[along]]]
+            };
+            for span in spans {
+                let mut rect = Rect {
+                    min: [0.0; 2],
+                    max: [0.0; 2],
+                };
+                rect.min[along] = span[0];
+                rect.max[along] = span[1];
+                rect.min[across] = outside[0].min(outside[1]);
+                rect.max[across] = outside[0].max(outside[1]);
+                walls.push(rect);
+            }
+        }
+        let corner = self.layout.corner_size();
+        if corner > 0.0 {
+            for &x in &[-1.0, 1.0] {
+                for &y in &[-1.0, 1.0] {
+                    let inner = [x * (half[0] - corner), y * (half[1] - corner)];
+                    let outer = [x * half[0], y * half[1]];
+                    walls.push(Rect {
+                        min: [inner[0].min(outer[0]), inner[1].min(outer[1])],
+                        max: [inner[0].max(outer[0]), inner[1].max(outer[1])],
+                    });
+                }
+            }
+        }
+        walls
+    }
+
+    /// Where the arena's obstacles are, `alpha` of the way from the previous step to this one
+    pub fn obstacle_rects(&self, alpha: f32) -> Vec<Rect> {
+        let time = (self.ticks as f32 - 1.0 + alpha) / TICKS_PER_SECOND as f32;
+        self.arena()
+            .obstacles
+            .iter()
+            .map(|obstacle| obstacle.rect_at(time))
+            .collect()
+    }
+
+    pub fn step(&mut self, inputs: &Inputs) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        self.ticks += 1;
+        for ball in self.balls.iter_mut() {
+            ball.previous_position = ball.position;
+            ball.previous_angle = ball.angle;
+        }
+        for paddle in self.paddles.iter_mut() {
+            paddle.previous_displacement = paddle.displacement;
+        }
+
+        for &player in self.players() {
+            let inverted = self.effects.controls_inverted(player);
+            let travel = self.paddle_travel(player);
+            let edge = self.edge(player);
+            let paddle = &mut self.paddles[player.index()];
+            for &action in &inputs.players[player.index()] {
+                paddle.apply(action);
+            }
+            paddle.update(edge, travel, &self.config.paddle_movement, inverted);
+        }
+
+        if !self.over {
+            self.run_clock(&mut events);
+        }
+        // Hold the ball in the middle while the winner is shown or it waits to be served
+        if !self.over {
+            match self.serve_countdown {
+                Some(ticks) if ticks > 0 && !inputs.serve => self.serve_countdown = Some(ticks - 1),
+                Some(_) => self.serve(&mut events),
+                None => {
+                    self.effects.tick();
+                    for index in 0..self.balls.len() {
+                        self.move_ball(index, &mut events);
+                    }
+                    self.break_bricks(&mut events);
+                    self.move_power_ups();
+                    self.collect_power_ups(&mut events);
+                    self.score_points(&mut events);
+                    self.add_ball(&mut events);
+                    self.spawn_power_up();
+                }
+            }
+        }
+
+        self.stats.record(&events);
+        events
+    }
+
+    // Timed matches count down every step until time runs out
+    fn run_clock(&mut self, events: &mut Vec<GameEvent>) {
+        match self.clock {
+            Some(ticks) if ticks > 1 => self.clock = Some(ticks - 1),
+            Some(1) => {
+                self.clock = Some(0);
+                // Bricks have to be cleared before time runs out
+                if self.layout == Layout::Bricks {
+                    events.push(GameEvent::MatchLost);
+                    self.finish(None);
+                    return;
+                }
+                match self.leader() {
+                    Some(winner) => {
+                        events.push(GameEvent::MatchWon(winner));
+                        self.finish(Some(winner));
+                    }
+                    None => events.push(GameEvent::SuddenDeath),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Whoever is ahead on their own, on points or on lives left
+    fn leader(&self) -> Option<Player> {
+        let rules = &self.config.rules;
+        let standings: Vec<_> = Edge::ALL
+            .iter()
+            .filter_map(|&edge| self.player_at(edge))
+            .map(|player| {
+                let standing = if self.layout.has_lives() {
+                    self.score.lives_left(player, rules)
+                } else {
+                    self.score.points(player)
+                };
+                (player, standing)
+            })
+            .collect();
+        let best = standings.iter().map(|&(_, standing)| standing).max()?;
+        let mut leaders = standings.iter().filter(|&&(_, standing)| standing == best);
+        match (leaders.next(), leaders.next()) {
+            (Some(&(player, _)), None) => Some(player),
+            _ => None,
+        }
+    }
+
+    // Leave the winner up with the ball back in the middle
+    fn finish(&mut self, winner: Option<Player>) {
+        self.wait_to_serve();
+        self.winner = winner;
+        self.over = true;
+        self.serve_countdown = None;
+    }
+
+    fn wait_to_serve(&mut self) {
+        self.balls = vec![Ball::new([0.0, 0.0])];
+        self.rally_ticks = 0;
+        self.rally_hits = 0;
+        let ticks = self.config.rules.serve_delay * TICKS_PER_SECOND as f32;
+        self.serve_countdown = Some(ticks as u32);
+    }
+
+    fn serve(&mut self, events: &mut Vec<GameEvent>) {
+        let active = self.active_players();
+        let two_ends = self.layout.has_two_ends();
+        let receiver = match (
+            self.config.rules.serve_to,
+            self.last_conceder,
+            self.last_receiver,
+        ) {
+            (ServeTo::Loser, Some(conceder), _) if active.contains(&conceder) => conceder,
+            (ServeTo::Winner, Some(conceder), _) if two_ends => conceder.other(),
+            // Round everyone still in, in turn
+            (ServeTo::Alternate, _, Some(receiver)) => active
+                .iter()
+                .cloned()
+                .find(|player| player.index() > receiver.index())
+                .unwrap_or(active[0]),
+            // Nobody has scored yet, or it's left to chance
+            _ => self.random_player(),
+        };
+        self.balls[0].velocity = self.launch(receiver);
+        self.serve_countdown = None;
+        self.last_receiver = Some(receiver);
+        events.push(GameEvent::Served(receiver));
+    }
+
+    // Multiball adds another ball from the middle once the rally has gone on long enough
+    fn add_ball(&mut self, events: &mut Vec<GameEvent>) {
+        let multiball = &self.config.multiball;
+        if self.balls.len() >= multiball.max_balls as usize {
+            return;
+        }
+        self.rally_ticks += 1;
+        let spawn_ticks = (multiball.spawn_delay * TICKS_PER_SECOND as f32) as u32;
+        let timed_out = multiball.spawn_delay > 0.0 && self.rally_ticks >= spawn_ticks;
+        let enough_hits = multiball.spawn_hits > 0 && self.rally_hits >= multiball.spawn_hits;
+        if !timed_out && !enough_hits {
+            return;
+        }
+        self.rally_ticks = 0;
+        self.rally_hits = 0;
+        let receiver = self.random_player();
+        let mut ball = Ball::new([0.0, 0.0]);
+        ball.velocity = self.launch(receiver);
+        self.balls.push(ball);
+        events.push(GameEvent::BallAdded);
+    }
+
+    fn random_player(&mut self) -> Player {
+        let active = self.active_players();
+        active[self.rng.gen_range(0, active.len())]
+    }
+
+    // Power-ups appear at random spots in the middle of the court every so often during a rally
+    fn spawn_power_up(&mut self) {
+        let config = &self.config.power_ups;
+        if !config.enabled || self.power_ups.len() >= config.max_on_court as usize {
+            return;
+        }
+        self.power_up_ticks += 1;
+        if self.power_up_ticks < (config.spawn_delay * TICKS_PER_SECOND as f32) as u32 {
+            return;
+        }
+        self.power_up_ticks = 0;
+        let kind = PowerUpKind::ALL[self.rng.gen_range(0, PowerUpKind::ALL.len())];
+        let reach = [
+            self.config.arena.half_width / 2.0,
+            self.config.arena.half_height * 0.8,
+        ];
+        let position = [
+            self.rng.gen_range(-reach[0], reach[0]),
+            self.rng.gen_range(-reach[1], reach[1]),
+        ];
+        self.power_ups.push(PowerUp {
+            kind,
+            position,
+            velocity: [0.0; 2],
+        });
+    }
+
+    // Clear away bricks that took their last hit, some leaving a power-up to fall towards the paddle
+    fn break_bricks(&mut self, events: &mut Vec<GameEvent>) {
+        let mut index = 0;
+        while index < self.bricks.len() {
+            let brick = self.bricks[index];
+            if brick.hits_left > 0 {
+                index += 1;
+                continue;
+            }
+            self.bricks.remove(index);
+            events.push(GameEvent::BrickBroken);
+            if brick.drops_power_up {
+                let kind = PowerUpKind::ALL[self.rng.gen_range(0, PowerUpKind::ALL.len())];
+                let rect = brick.rect;
+                let edge = self.edge(Player::One);
+                let mut velocity = [0.0; 2];
+                velocity[1 - edge.axis()] = edge.side() * DROP_SPEED;
+                self.power_ups.push(PowerUp {
+                    kind,
+                    position: [
+                        (rect.min[0] + rect.max[0]) / 2.0,
+                        (rect.min[1] + rect.max[1]) / 2.0,
+                    ],
+                    velocity,
+                });
+            }
+        }
+    }
+
+    // Falling power-ups that nobody catches are lost off the court
+    fn move_power_ups(&mut self) {
+        let half = self.half_size();
+        for power_up in self.power_ups.iter_mut() {
+            power_up.position[0] += power_up.velocity[0];
+            power_up.position[1] += power_up.velocity[1];
+        }
+        self.power_ups.retain(|power_up| {
+            power_up.position[0].abs() < half[0] + POWER_UP_HALF_SIZE
+                && power_up.position[1].abs() < half[1] + POWER_UP_HALF_SIZE
+        });
+    }
+
+    // A ball passing through a power-up picks it up for whoever sent the ball that way
+    fn collect_power_ups(&mut self, events: &mut Vec<GameEvent>) {
+        let mut index = 0;
+        while index < self.power_ups.len() {
+            let power_up = self.power_ups[index];
+            let rect = power_up.rect();
+            let ball = self
+                .balls
+                .iter()
+                .find(|ball| collision::overlaps(ball.position, BALL_HALF_SIZE, &rect))
+                .cloned();
+            // Paddles can catch pickups falling towards them, with the first ball splitting.
+            // Ones sitting still on the court need a ball sent through them.
+            let falling = power_up.velocity != [0.0; 2];
+            let caught = self.active_players().into_iter().find(|&player| {
+                let paddle = self.paddle_rect(player);
+                falling && collision::overlaps(power_up.position, POWER_UP_HALF_SIZE, &paddle)
+            });
+            // A ball nobody has hit yet goes straight through
+            let (collector, ball) = match (ball.and_then(|ball| ball.last_hit), caught) {
+                (Some(player), _) => (player, ball.unwrap()),
+                (None, Some(player)) if !self.balls.is_empty() => (player, self.balls[0]),
+                _ => {
+                    index += 1;
+                    continue;
+                }
+            };
+            let kind = self.power_ups.remove(index).kind;
+            if kind == PowerUpKind::SplitBall {
+                // The new ball heads off at the mirror image of the old one
+                let mut split = ball;
+                let along = if ball.velocity[0].abs() > ball.velocity[1].abs() {
+                    1
+                } else {
+                    0
+                };
+                split.velocity[along] = -split.velocity[along];
+                self.balls.push(split);
+            } else {
+                let ticks = (self.config.power_ups.duration * TICKS_PER_SECOND as f32) as u32;
+                // Effects against opponents don't land on the collector's partner, and land on the
+                // collector when there's nobody else
+                let end = self.edge(collector);
+                let mut targets: Vec<_> = self
+                    .active_players()
+                    .into_iter()
+                    .filter(|&player| self.edge(player) != end)
+                    .collect();
+                if !kind.against_opponents() || targets.is_empty() {
+                    targets = vec![collector];
+                }
+                for player in targets {
+                    self.effects.add(kind, player, ticks);
+                }
+            }
+            events.push(GameEvent::PowerUpCollected(collector, kind));
+        }
+    }
+
+    // Velocity for a ball sent from the middle towards `receiver` at a random angle
+    fn launch(&mut self, receiver: Player) -> [f32; 2] {
+        let edge = self.edge(receiver);
+        let angle = self
+            .rng
+            .gen_range(-MAX_SERVE_ANGLE, MAX_SERVE_ANGLE)
+            .to_radians();
+        let speed = self.config.ball_speed;
+        let mut velocity = [0.0; 2];
+        velocity[1 - edge.axis()] = edge.side() * speed * angle.cos();
+        velocity[edge.axis()] = speed * angle.sin();
+        velocity
+    }
+
+    // Move a ball for one step, bouncing off anything it sweeps into on the way
+    fn move_ball(&mut self, index: usize, events: &mut Vec<GameEvent>) {
+        // Everything the ball can bounce off, and who it belongs to
+        let mut obstacles: Vec<(Owner, Rect)> = self
+            .walls()
+            .into_iter()
+            .chain(self.obstacle_rects(1.0))
+            .map(|rect| (Owner::Wall, rect))
+            .collect();
+        // Bricks another ball already broke this step are cleared away after every ball has moved
+        for (
+            brick,
+            Brick {
+                rect, hits_left, ..
+            },
+        ) in self.bricks.iter().enumerate()
+        {
+            if *hits_left > 0 {
+                obstacles.push((Owner::Brick(brick), *rect));
+            }
+        }
+        // and how fast each paddle is moving across the court, for spinning the ball
+        let mut paddle_velocities = [[0.0; 2]; MAX_PLAYERS];
+        for player in self.active_players() {
+            obstacles.push((Owner::Paddle(player), self.paddle_rect(player)));
+            let edge = self.edge(player);
+            paddle_velocities[player.index()][edge.axis()] =
+                edge.forward() * self.paddle(player).velocity;
+        }
+        let layout = self.layout;
+        let spin = &self.config.spin;
+
+        // Power-ups can make every ball cover more or less ground each step
+        let scale = self.effects.ball_speed_scale();
+        let ball = &mut self.balls[index];
+        if spin.enabled {
+            if let Some(player) = ball.last_hit {
+                let across = 1 - layout.edge(player).axis();
+                ball.curve(spin, across, self.config.max_bounce_angle);
+            }
+        }
+        let mut remaining = scale;
+        for _ in 0..MAX_BOUNCES {
+            let delta = [ball.velocity[0] * remaining, ball.velocity[1] * remaining];
+            // Paddles only stop balls heading for their goal, so shots from the back go through the front
+            let first = obstacles
+                .iter()
+                .filter(|(owner, _)| match owner {
+                    Owner::Paddle(player) => {
+                        let edge = layout.edge(*player);
+                        ball.velocity[1 - edge.axis()] * edge.side() >= 0.0
+                    }
+                    _ => true,
+                })
+                .filter_map(|(owner, rect)| {
+                    collision::sweep(ball.position, delta, BALL_HALF_SIZE, rect)
+                        .map(|contact| (*owner, *rect, contact))
+                })
+                .min_by(|a, b| a.2.time.partial_cmp(&b.2.time).unwrap());
+
+            let (owner, rect, contact) = match first {
+                Some(hit) => hit,
+                None => {
+                    ball.position[0] += delta[0];
+                    ball.position[1] += delta[1];
+                    return;
+                }
+            };
+            ball.position = contact.position;
+            // Hits on the face of a paddle get aimed, anything else just bounces
+            ball.velocity = match owner {
+                Owner::Paddle(player) if contact.normal[1 - layout.edge(player).axis()] != 0.0 => {
+                    let speed = (speed(ball.velocity) + self.config.ball_speed_increase)
+                        .min(self.config.max_ball_speed);
+                    let axis = layout.edge(player).axis();
+                    if spin.enabled {
+                        // The paddle's face drags the side of the ball it touches along with it
+                        let moving = paddle_velocities[player.index()];
+                        let normal = contact.normal;
+                        ball.spin = ball.spin * (1.0 - spin.grip)
+                            + spin.transfer * (moving[0] * normal[1] - moving[1] * normal[0]);
+                    }
+                    aim_off_paddle(speed, &contact, &rect, axis, self.config.max_bounce_angle)
+                }
+                _ => {
+                    ball.velocity = collision::reflect(ball.velocity, contact.normal);
+                    if spin.enabled {
+                        ball.grip(spin, contact.normal);
+                    }
+                    ball.velocity
+                }
+            };
+            remaining *= 1.0 - contact.time;
+            events.push(match owner {
+                Owner::Paddle(player) => {
+                    self.rally_hits += 1;
+                    if layout == Layout::Solo {
+                        self.score.streak += 1;
+                    }
+                    ball.last_hit = Some(player);
+                    GameEvent::PaddleHit(player)
+                }
+                Owner::Brick(brick) => {
+                    let brick = &mut self.bricks[brick];
+                    brick.hits_left -= 1;
+                    self.score.points[Player::One.index()] += 1;
+                    // A broken brick is out of the way for the rest of the step
+                    if brick.hits_left == 0 {
+                        obstacles.retain(|&(other, _)| other != owner);
+                    }
+                    GameEvent::BrickHit
+                }
+                Owner::Wall => GameEvent::WallBounce,
+            });
+        }
+    }
+
+    // Point Scoring, every ball that gets past a paddle counts
+    fn score_points(&mut self, events: &mut Vec<GameEvent>) {
+        if self.layout == Layout::Bricks && self.bricks.is_empty() {
+            events.push(GameEvent::MatchWon(Player::One));
+            self.finish(Some(Player::One));
+            return;
+        }
+        let mut index = 0;
+        while index < self.balls.len() {
+            let conceder = match self.goal_crossed(&self.balls[index]) {
+                Some(player) => player,
+                None => {
+                    index += 1;
+                    continue;
+                }
+            };
+            self.balls.remove(index);
+            self.last_conceder = Some(conceder);
+            let rules = &self.config.rules;
+            // The two ends score points off each other, or everyone loses lives until one is left,
+            // or practice starts counting again
+            let (outcome, winner) = if self.layout == Layout::Solo {
+                events.push(GameEvent::StreakEnded(self.score.streak));
+                self.score.streak = 0;
+                self.score.points_played += 1;
+                (Outcome::Point, conceder)
+            } else if self.layout == Layout::Bricks {
+                events.push(GameEvent::LifeLost);
+                let mut outcome = self.score.lose_life(conceder, rules);
+                if outcome == Outcome::Eliminated {
+                    outcome = Outcome::MatchLost;
+                    events.push(GameEvent::MatchLost);
+                }
+                (outcome, conceder)
+            } else if !self.layout.has_lives() {
+                let scorer = conceder.other();
+                events.push(GameEvent::PointScored(scorer));
+                let outcome = self.score.award_point(scorer, rules);
+                match outcome {
+                    Outcome::SetWon => events.push(GameEvent::SetWon(scorer)),
+                    Outcome::MatchWon => events.push(GameEvent::MatchWon(scorer)),
+                    _ => (),
+                }
+                (outcome, scorer)
+            } else {
+                events.push(GameEvent::LifeLost);
+                let mut outcome = self.score.lose_life(conceder, rules);
+                let active = self.active_players();
+                if outcome == Outcome::Eliminated {
+                    events.push(GameEvent::Eliminated(conceder));
+                    if active.len() == 1 {
+                        outcome = Outcome::MatchWon;
+                        events.push(GameEvent::MatchWon(active[0]));
+                    }
+                }
+                (outcome, active[0])
+            };
+            // In sudden death the first to get ahead wins
+            let (outcome, winner) = match self.leader() {
+                Some(leader) if self.is_sudden_death() && outcome != Outcome::MatchWon => {
+                    events.push(GameEvent::MatchWon(leader));
+                    (Outcome::MatchWon, leader)
+                }
+                _ => (outcome, winner),
+            };
+            // The rally carries on while there are balls left, unless the set is over or someone's out
+            if outcome == Outcome::MatchWon {
+                self.finish(Some(winner));
+                return;
+            }
+            if outcome == Outcome::MatchLost {
+                self.finish(None);
+                return;
+            }
+            if outcome != Outcome::Point || self.balls.is_empty() {
+                self.wait_to_serve();
+                return;
+            }
+        }
+    }
+
+    // Whose goal a ball has gone past, if it's left the court
+    fn goal_crossed(&self, ball: &Ball) -> Option<Player> {
+        let half = self.half_size();
+        Edge::ALL
+            .iter()
+            .filter(|edge| {
+                let across = 1 - edge.axis();
+                ball.position[across] * edge.side() >= half[across]
+            })
+            .find_map(|&edge| self.player_at(edge))
+    }
+}
+
+// What a ball bounced off
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Owner {
+    Wall,
+    Paddle(Player),
+    // By its index into the bricks still standing
+    Brick(usize),
+}
+
+// The further from the centre of the paddle the ball hits, the steeper it leaves, up to `max_angle` degrees.
+// `along` is the axis the paddle slides along.
+fn aim_off_paddle(
+    speed: f32,
+    contact: &Contact,
+    paddle: &Rect,
+    along: usize,
+    max_angle: f32,
+) -> [f32; 2] {
+    let across = 1 - along;
+    let centre = (paddle.min[along] + paddle.max[along]) / 2.0;
+    let reach = (paddle.max[along] - paddle.min[along]) / 2.0 + BALL_HALF_SIZE;
+    let offset = ((contact.point[along] - centre) / reach).clamp(-1.0, 1.0);
+    let angle = (offset * max_angle).to_radians();
+    let mut velocity = [0.0; 2];
+    velocity[across] = contact.normal[across] * speed * angle.cos();
+    velocity[along] = speed * angle.sin();
+    velocity
+}
+
+fn speed(velocity: [f32; 2]) -> f32 {
+    velocity[0].hypot(velocity[1])
+}
+
+// Turn a velocity `angle` radians, clockwise on screen
+fn rotate(velocity: [f32; 2], angle: f32) -> [f32; 2] {
+    let (sin, cos) = angle.sin_cos();
+    [
+        velocity[0] * cos - velocity[1] * sin,
+        velocity[0] * sin + velocity[1] * cos,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::Arena;
+    use crate::level::Level;
+    use crate::rules::MatchRules;
+
+    fn step_until_served(game: &mut GameState, inputs: &Inputs) -> (u32, Player) {
+        for steps in 1..1000 {
+            for event in game.step(inputs) {
+                if let GameEvent::Served(receiver) = event {
+                    return (steps, receiver);
+                }
+            }
+        }
+        panic!("the ball was never served");
+    }
+
+    #[test]
+    fn ball_waits_for_the_countdown_or_the_serve_key() {
+        let mut game = GameState::new(GameConfig::default(), Layout::Singles, 1);
+        let (steps, _) = step_until_served(&mut game, &Inputs::default());
+        assert_eq!(steps, 3 * TICKS_PER_SECOND + 1);
+        assert_ne!(game.balls[0].velocity, [0.0; 2]);
+
+        let mut game = GameState::new(GameConfig::default(), Layout::Singles, 1);
+        let serve = Inputs {
+            serve: true,
+            ..Inputs::default()
+        };
+        assert_eq!(step_until_served(&mut game, &serve).0, 1);
+    }
+
+    #[test]
+    fn ball_past_a_paddle_scores_and_is_served_to_the_loser() {
+        let mut game = GameState::new(GameConfig::default(), Layout::Singles, 1);
+        game.serve_countdown = None;
+        // Well clear of player 2's paddle, heading for their goal
+        let mut ball = Ball::new([90.0, 80.0]);
+        ball.velocity = [5.0, 0.0];
+        game.balls = vec![ball];
+
+        let mut scored = Vec::new();
+        for _ in 0..5 {
+            for event in game.step(&Inputs::default()) {
+                if let GameEvent::PointScored(scorer) = event {
+                    scored.push(scorer);
+                }
+            }
+        }
+        assert_eq!(scored, [Player::One]);
+        assert_eq!(game.score.points(Player::One), 1);
+        // Back in the middle waiting to go to whoever let it past
+        assert_eq!(game.balls[0].position, [0.0, 0.0]);
+        assert!(game.serve_countdown.is_some());
+        let (_, receiver) = step_until_served(&mut game, &Inputs::default());
+        assert_eq!(receiver, Player::Two);
+    }
+
+    // Send the only ball into the goal `player` is guarding, from well clear of their paddle
+    fn score_past(game: &mut GameState, player: Player) -> Vec<GameEvent> {
+        game.serve_countdown = None;
+        let side = game.edge(player).side();
+        let mut ball = Ball::new([side * 90.0, 80.0]);
+        ball.velocity = [side * 5.0, 0.0];
+        game.balls = vec![ball];
+        (0..5).flat_map(|_| game.step(&Inputs::default())).collect()
+    }
+
+    fn timed_game() -> GameState {
+        let config = GameConfig {
+            rules: MatchRules {
+                time_limit: 60.0,
+                ..MatchRules::default()
+            },
+            ..GameConfig::default()
+        };
+        GameState::new(config, Layout::Singles, 1)
+    }
+
+    #[test]
+    fn whoever_is_ahead_when_time_runs_out_wins() {
+        let mut game = timed_game();
+        score_past(&mut game, Player::Two);
+        game.clock = Some(1);
+        let events = game.step(&Inputs::default());
+        assert_eq!(events, [GameEvent::MatchWon(Player::One)]);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn a_level_match_goes_to_sudden_death_and_the_next_point_wins() {
+        let mut game = timed_game();
+        score_past(&mut game, Player::Two);
+        score_past(&mut game, Player::One);
+        game.clock = Some(1);
+        let events = game.step(&Inputs::default());
+        assert_eq!(events, [GameEvent::SuddenDeath]);
+        assert!(game.is_sudden_death() && !game.is_over());
+
+        let events = score_past(&mut game, Player::One);
+        assert!(events.contains(&GameEvent::PointScored(Player::Two)));
+        assert!(events.contains(&GameEvent::MatchWon(Player::Two)));
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn momentum_paddles_speed_up_then_coast_to_a_stop() {
+        let movement = PaddleMovement::default();
+        let mut paddle = Paddle::default();
+        paddle.apply(PaddleAction::Press(Direction::Down));
+        for _ in 0..3 {
+            paddle.update(Edge::Left, 100.0, &movement, false);
+        }
+        assert_eq!((paddle.velocity, paddle.displacement), (3.0, 6.0));
+        for _ in 0..5 {
+            paddle.update(Edge::Left, 100.0, &movement, false);
+        }
+        assert_eq!(paddle.velocity, movement.max_speed);
+
+        // Friction slows it down, but never turns it around
+        paddle.apply(PaddleAction::Release(Direction::Down));
+        let mut last = paddle.displacement;
+        for _ in 0..10 {
+            paddle.update(Edge::Left, 100.0, &movement, false);
+            assert!(paddle.velocity >= 0.0 && paddle.displacement >= last);
+            last = paddle.displacement;
+        }
+        assert_eq!(paddle.velocity, 0.0);
+    }
+
+    #[test]
+    fn instant_paddles_stop_dead_at_either_end() {
+        let movement = PaddleMovement {
+            model: MovementModel::Instant,
+            ..PaddleMovement::default()
+        };
+        let mut paddle = Paddle::default();
+        paddle.apply(PaddleAction::Press(Direction::Down));
+        paddle.update(Edge::Left, 50.0, &movement, false);
+        assert_eq!(paddle.velocity, movement.max_speed);
+        for _ in 0..20 {
+            paddle.update(Edge::Left, 50.0, &movement, false);
+        }
+        assert_eq!((paddle.velocity, paddle.displacement), (0.0, 50.0));
+
+        // Inverted controls send it back the other way, as far as where it started
+        for _ in 0..20 {
+            paddle.update(Edge::Left, 50.0, &movement, true);
+        }
+        assert_eq!((paddle.velocity, paddle.displacement), (0.0, 0.0));
+    }
+
+    #[test]
+    fn paddles_only_catch_falling_power_ups() {
+        let mut game = GameState::new(GameConfig::default(), Layout::FourWay, 1);
+        game.serve_countdown = None;
+        let rect = game.paddle_rect(Player::Three);
+        let centre = [
+            (rect.min[0] + rect.max[0]) / 2.0,
+            (rect.min[1] + rect.max[1]) / 2.0,
+        ];
+        let sitting = PowerUp {
+            kind: PowerUpKind::SplitBall,
+            position: centre,
+            velocity: [0.0; 2],
+        };
+        game.power_ups = vec![sitting];
+        let events = game.step(&Inputs::default());
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, GameEvent::PowerUpCollected(..))));
+        assert_eq!(game.power_ups.len(), 1);
+        assert_eq!(game.balls.len(), 1);
+
+        game.power_ups[0].velocity = [0.0, -0.1];
+        let events = game.step(&Inputs::default());
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::PowerUpCollected(Player::Three, PowerUpKind::SplitBall)
+        )));
+        assert_eq!(game.balls.len(), 2);
+    }
+
+    #[test]
+    fn four_player_paddles_have_to_fit_between_the_corners() {
+        let config = GameConfig {
+            arena: Arena {
+                half_height: 40.0,
+                ..Arena::default()
+            },
+            ..GameConfig::default()
+        };
+        assert!(Layout::Singles.validate(&config).is_ok());
+        assert!(Layout::FourWay.validate(&config).is_err());
+        // and nothing goes wrong if a match is started anyway
+        let mut game = GameState::new(config, Layout::FourWay, 1);
+        game.step(&Inputs::default());
+        assert_eq!(game.paddle_travel(Player::One), 0.0);
+    }
+
+    #[test]
+    fn paddles_of_any_size_reach_both_ends_of_their_goal() {
+        let mut game = GameState::new(GameConfig::default(), Layout::Singles, 1);
+        game.effects
+            .add(PowerUpKind::ShrinkPaddle, Player::One, 100);
+        game.effects.add(PowerUpKind::GrowPaddle, Player::Two, 100);
+        let end = game.arena().half_height;
+        for &player in [Player::One, Player::Two].iter() {
+            game.paddles[player.index()].displacement = 0.0;
+            let rest = game.paddle_rect(player);
+            game.paddles[player.index()].displacement = game.paddle_travel(player);
+            let moved = game.paddle_rect(player);
+            let (low, high) = if rest.min[1] < moved.min[1] {
+                (rest, moved)
+            } else {
+                (moved, rest)
+            };
+            assert_eq!((low.min[1], high.max[1]), (-end, end), "{:?}", player);
+        }
+    }
+
+    #[test]
+    fn two_balls_hitting_a_brick_at_once_break_it_once() {
+        let config = GameConfig {
+            level: Level {
+                origin: [40.0, -8.0],
+                rows: vec!["1".to_string()],
+                ..Level::default()
+            },
+            ..GameConfig::default()
+        };
+        let mut game = GameState::new(config, Layout::Bricks, 1);
+        game.serve_countdown = None;
+        game.balls = [-4.0, 4.0]
+            .iter()
+            .map(|&y| {
+                let mut ball = Ball::new([30.0, y]);
+                ball.velocity = [2.0, 0.0];
+                ball
+            })
+            .collect();
+
+        let mut hits = 0;
+        let mut broken = 0;
+        for _ in 0..10 {
+            for event in game.step(&Inputs::default()) {
+                match event {
+                    GameEvent::BrickHit => hits += 1,
+                    GameEvent::BrickBroken => broken += 1,
+                    _ => (),
+                }
+            }
+        }
+        assert_eq!(hits, 1);
+        assert_eq!(broken, 1);
+        assert_eq!(game.score.points(Player::One), 1);
+        // Clearing the only brick wins
+        assert_eq!(game.winner, Some(Player::One));
+    }
+}
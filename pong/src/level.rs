@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+
+use crate::arena::Arena;
+use crate::collision::{self, Rect};
+use crate::game::BALL_HALF_SIZE;
+
+/// A wall of bricks to knock down, anything missing from a level file is left at its default
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Level {
+    // Top left corner of the grid
+    pub origin: [f32; 2],
+    pub brick_size: [f32; 2],
+    // Space between neighbouring bricks
+    pub gap: f32,
+    // The grid a row at a time from the top. '.' is no brick, 1 to 9 a brick taking that many hits
+    // to break and '*' a brick that drops a power-up when it breaks.
+    pub rows: Vec<String>,
+}
+
+impl Default for Level {
+    fn default() -> Level {
+        let rows = [
+            "112233", "11*233", "112233", "1*2233", "112233", "112233", "1*2233", "112233",
+            "11*233", "112233",
+        ];
+        Level {
+            origin: [20.0, -90.0],
+            brick_size: [8.0, 16.0],
+            gap: 2.0,
+            rows: rows.iter().map(|row| row.to_string()).collect(),
+        }
+    }
+}
+
+/// A brick still standing
+#[derive(Clone, Copy, Debug)]
+pub struct Brick {
+    pub rect: Rect,
+    pub hits_left: u32,
+    pub drops_power_up: bool,
+}
+
+impl Level {
+    /// Read a level from a RON file
+    pub fn load(path: &Path) -> Result<Level, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        ron::de::from_str(&text).map_err(|err| format!("bad level {}: {}", path.display(), err))
+    }
+
+    /// Every brick in the grid, where it is on the court
+    pub fn bricks(&self) -> Vec<Brick> {
+        let step = [self.brick_size[0] + self.gap, self.brick_size[1] + self.gap];
+        let mut bricks = Vec::new();
+        for (row, line) in self.rows.iter().enumerate() {
+            for (column, cell) in line.chars().enumerate() {
+                let (hits_left, drops_power_up) = match cell {
+                    '*' => (1, true),
+                    _ => match cell.to_digit(10) {
+                        Some(hits) if hits > 0 => (hits, false),
+                        _ => continue,
+                    },
+                };
+                let min = [
+                    self.origin[0] + column as f32 * step[0],
+                    self.origin[1] + row as f32 * step[1],
+                ];
+                let max = [min[0] + self.brick_size[0], min[1] + self.brick_size[1]];
+                bricks.push(Brick {
+                    rect: Rect { min, max },
+                    hits_left,
+                    drops_power_up,
+                });
+            }
+        }
+        bricks
+    }
+
+    /// Check the grid makes sense and fits on the court
+    pub fn validate(&self, arena: &Arena) -> Result<(), String> {
+        if self.brick_size[0] <= 0.0 || self.brick_size[1] <= 0.0 {
+            return Err("bricks must be bigger than 0".to_string());
+        }
+        if self.gap < 0.0 {
+            return Err("the gap between bricks can't be negative".to_string());
+        }
+        let allowed = |cell: char| cell == '.' || cell == '*' || ('1'..='9').contains(&cell);
+        if self.rows.iter().any(|row| !row.chars().all(allowed)) {
+            return Err("level rows can only have '.', '*' and 1 to 9 in them".to_string());
+        }
+        let bricks = self.bricks();
+        if bricks.is_empty() {
+            return Err("a level needs at least 1 brick".to_string());
+        }
+        for brick in &bricks {
+            let rect = &brick.rect;
+            if rect.min[0] < -arena.half_width
+                || rect.max[0] > arena.half_width
+                || rect.min[1] < -arena.half_height
+                || rect.max[1] > arena.half_height
+            {
+                return Err("bricks must all be on the court".to_string());
+            }
+            // The ball is served from the middle
+            if collision::overlaps([0.0, 0.0], BALL_HALF_SIZE, rect) {
+                return Err("bricks can't cover the middle of the court".to_string());
+            }
+        }
+        Ok(())
+    }
+}
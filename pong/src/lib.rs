@@ -0,0 +1,20 @@
+//! The game itself, without a window or anything drawn, so it can be played headless by
+//! bots, servers and tests as well as by the `pong` binary
+
+pub mod ai;
+pub mod arena;
+pub mod camera;
+pub mod collision;
+pub mod config;
+pub mod game;
+pub mod level;
+pub mod menu;
+pub mod mode;
+pub mod options;
+pub mod powerup;
+pub mod records;
+pub mod replay;
+pub mod rules;
+pub mod screen;
+pub mod stats;
+pub mod timestep;
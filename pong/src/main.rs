@@ -4,7 +4,7 @@ use vulkano::device::{Device, DeviceExtensions};
 use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, RenderPassAbstract, Subpass};
 use vulkano::image::SwapchainImage;
 use vulkano::instance::{Instance, PhysicalDevice};
-use vulkano::pipeline::viewport::Viewport;
+use vulkano::pipeline::viewport::{Scissor, Viewport};
 use vulkano::pipeline::GraphicsPipeline;
 use vulkano::swapchain;
 use vulkano::swapchain::{
@@ -19,12 +19,33 @@ use winit::{
     WindowEvent,
 };
 
-use rand::Rng;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
 use vulkano_text::{DrawText, DrawTextTrait};
 
+use pong::camera::Camera;
+use pong::game::{
+    Direction, Edge, GameEvent, GameState, Inputs, Layout, PaddleAction, Player, BALL_HALF_SIZE,
+};
+use pong::level::Brick;
+use pong::menu::{Menu, MenuKey};
+use pong::options::Options;
+use pong::powerup::{PowerUpKind, POWER_UP_HALF_SIZE};
+use pong::records::{Records, RECORDS_FILE};
+use pong::replay::{Playback, Recorder, Replay};
+use pong::screen::Screen;
+use pong::timestep::{FixedTimestep, TICKS_PER_SECOND};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
 fn main() {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
     // Create a Vulkan Instance and selecting extensions to enable
     let extensions = vulkano_win::required_extensions();
     let instance = Instance::new(None, &extensions, None).expect("failed to create instance");
@@ -118,68 +139,68 @@ fn main() {
 
     // Create the Vertex and Fragment Shaders
 
-    mod vs_player1 {
+    mod vs_paddle {
         vulkano_shaders::shader! {
             ty: "vertex",
-            path: "src/shaders/player1.vs"
+            path: "src/shaders/paddle.vs"
         }
     }
 
-    mod fs_player1 {
+    mod fs_paddle {
         vulkano_shaders::shader! {
             ty: "fragment",
-            path: "src/shaders/player1.fs"
+            path: "src/shaders/paddle.fs"
         }
     }
 
-    mod vs_player2 {
+    mod vs_net {
         vulkano_shaders::shader! {
             ty: "vertex",
-            path: "src/shaders/player2.vs"
+            path: "src/shaders/net.vs"
         }
     }
 
-    mod fs_player2 {
+    mod fs_net {
         vulkano_shaders::shader! {
             ty: "fragment",
-            path: "src/shaders/player2.fs"
+            path: "src/shaders/net.fs"
         }
     }
-
-    mod vs_net {
+    mod vs_ball {
         vulkano_shaders::shader! {
             ty: "vertex",
-            path: "src/shaders/net.vs"
+            path: "src/shaders/ball.vs"
         }
     }
 
-    mod fs_net {
+    mod fs_ball {
         vulkano_shaders::shader! {
             ty: "fragment",
-            path: "src/shaders/net.fs"
+            path: "src/shaders/ball.fs"
         }
     }
-    mod vs_ball {
+
+    mod vs_powerup {
         vulkano_shaders::shader! {
             ty: "vertex",
-            path: "src/shaders/ball.vs"
+            path: "src/shaders/powerup.vs"
         }
     }
 
-    mod fs_ball {
+    mod fs_powerup {
         vulkano_shaders::shader! {
             ty: "fragment",
-            path: "src/shaders/ball.fs"
+            path: "src/shaders/powerup.fs"
         }
     }
-    let vs_player1 = vs_player1::Shader::load(device.clone()).unwrap();
-    let fs_player1 = fs_player1::Shader::load(device.clone()).unwrap();
-    let vs_player2 = vs_player2::Shader::load(device.clone()).unwrap();
-    let fs_player2 = fs_player2::Shader::load(device.clone()).unwrap();
+    let vs_paddle = vs_paddle::Shader::load(device.clone()).unwrap();
+    let fs_paddle = fs_paddle::Shader::load(device.clone()).unwrap();
     let vs_net = vs_net::Shader::load(device.clone()).unwrap();
     let fs_net = fs_net::Shader::load(device.clone()).unwrap();
     let vs_ball = vs_ball::Shader::load(device.clone()).unwrap();
     let fs_ball = fs_ball::Shader::load(device.clone()).unwrap();
+    let vs_powerup = vs_powerup::Shader::load(device.clone()).unwrap();
+    let fs_powerup = fs_powerup::Shader::load(device.clone()).unwrap();
     // Create Render Pass
     let render_pass = Arc::new(
         vulkano::single_pass_renderpass!(
@@ -205,49 +226,49 @@ fn main() {
     let (width, _): (u32, u32) = surface.window().get_inner_size().unwrap().into();
     let mut x = -200.0;
 
-    let pipeline = Arc::new(
+    let pipeline_paddle = Arc::new(
         GraphicsPipeline::start()
             .vertex_input_single_buffer()
-            .vertex_shader(vs_player1.main_entry_point(), ())
+            .vertex_shader(vs_paddle.main_entry_point(), ())
             .triangle_list()
-            .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs_player1.main_entry_point(), ())
+            .viewports_scissors_dynamic(1)
+            .fragment_shader(fs_paddle.main_entry_point(), ())
             .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
             .build(device.clone())
             .unwrap(),
     );
 
-    let pipeline_player2 = Arc::new(
+    let pipeline_net = Arc::new(
         GraphicsPipeline::start()
             .vertex_input_single_buffer()
-            .vertex_shader(vs_player2.main_entry_point(), ())
+            .vertex_shader(vs_net.main_entry_point(), ())
             .triangle_list()
-            .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs_player2.main_entry_point(), ())
+            .viewports_scissors_dynamic(1)
+            .fragment_shader(fs_net.main_entry_point(), ())
             .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
             .build(device.clone())
             .unwrap(),
     );
 
-    let pipeline_net = Arc::new(
+    let pipeline_ball = Arc::new(
         GraphicsPipeline::start()
             .vertex_input_single_buffer()
-            .vertex_shader(vs_net.main_entry_point(), ())
+            .vertex_shader(vs_ball.main_entry_point(), ())
             .triangle_list()
-            .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs_net.main_entry_point(), ())
+            .viewports_scissors_dynamic(1)
+            .fragment_shader(fs_ball.main_entry_point(), ())
             .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
             .build(device.clone())
             .unwrap(),
     );
 
-    let pipeline_ball = Arc::new(
+    let pipeline_powerup = Arc::new(
         GraphicsPipeline::start()
             .vertex_input_single_buffer()
-            .vertex_shader(vs_ball.main_entry_point(), ())
+            .vertex_shader(vs_powerup.main_entry_point(), ())
             .triangle_list()
-            .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs_ball.main_entry_point(), ())
+            .viewports_scissors_dynamic(1)
+            .fragment_shader(fs_powerup.main_entry_point(), ())
             .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
             .build(device.clone())
             .unwrap(),
@@ -261,177 +282,186 @@ fn main() {
         write_mask: None,
         reference: None,
     };
-    let mut framebuffers =
-        window_size_dependent_setup(&images, render_pass.clone(), &mut dynamic_state);
+    // The whole court in view
+    let camera = Camera::default();
+    let (mut framebuffers, mut projection) =
+        window_size_dependent_setup(&images, render_pass.clone(), &mut dynamic_state, &camera);
     let mut recreate_swapchain = false;
     let mut previous_frame_end = Box::new(sync::now(device.clone())) as Box<dyn GpuFuture>;
-    let mut player_1_displacement = 0;
-    let mut player_2_displacement = 0;
-    let mut player_1_displacement_velocity: i32 = 0;
-    let mut player_2_displacement_velocity: i32 = 0;
-    let mut displacement_increment = false;
-    // Random number generator for initial y position of ball and direction
-    let mut rng = rand::thread_rng();
-    let mut ball_displacement_x_increment: bool = rand::random();
-    let mut ball_displacement_y_increment: bool = rand::random();
-    let displacement_x_constant = 1;
-    let displacement_y_constant = 1;
-
-    let mut ball_displacement: [i32; 2] = [0; 2];
-    /*     fn displace_player(direction: String, displacement: i32) -> i32 {
-        if (displacement == 150 && direction == "Down") || (displacement == 0 && direction == "Up")
-        {
-            return displacement;
-        }
-        if direction == "Up" {
-            return displacement - 10;
-        } else if direction == "Down" {
-            return displacement + 10;
+    // Every match gets the seed from the command line if there is one
+    let new_seed = || options.seed.unwrap_or_else(rand::random);
+    let mut game = GameState::new(options.config.clone(), options.mode.layout(), new_seed());
+    // The computer players have their own generator so thinking doesn't change the game's
+    let mut cpu_rng = StdRng::seed_from_u64(game.seed());
+    // The title and pause screens have a menu to change who is playing,
+    // which only takes over from the mode being played once it's confirmed
+    let mut menu = Menu::new(options.mode, options.difficulty);
+    let mut mode = options.mode;
+    let mut cpu_players = options.mode.cpu_players(options.difficulty);
+    let mut inputs = Inputs::default();
+    let mut screen = Screen::Title;
+    let mut new_match = false;
+    // Why the match picked from the menu couldn't start
+    let mut menu_error: Option<String> = None;
+    // What the last power-up picked up was, and how many more steps to show it for
+    let mut announcement: Option<(String, u32)> = None;
+    // Personal bests from earlier runs, and whether there's a new one to save
+    let mut records = Records::load(Path::new(RECORDS_FILE)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        Records::default()
+    });
+    let mut records_changed = false;
+    let mut recorder: Option<Recorder> = None;
+    let mut playback: Option<Playback> = None;
+    if let Some(path) = &options.replay {
+        let replay = match Replay::load(path) {
+            Ok(replay) => replay,
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(2);
+            }
         };
-        displacement
-    } */
-
-    let mut score_player1: u8 = 0;
-    let mut score_player2: u8 = 0;
-    // Just experimentally found the position for the ball displacement so that it intersects with the paddle
-    let paddle_y_player1 = -77;
-    let paddle_y_player2 = 77;
-    let wall_y_top = -97;
-    let wall_y_bottom = 97;
-    let single_player = false;
-    // How long to hold the text of the winner for
-    let mut theres_a_winner = false;
-    let mut time_is_set = false;
-    let mut time: Instant = Instant::now();
+        game = GameState::new(replay.config.clone(), replay.layout, replay.seed);
+        playback = Some(Playback::new(replay));
+        screen = Screen::Playing;
+    }
+    // Simulate at a fixed rate however fast the display is refreshing
+    let mut timestep = FixedTimestep::new(TICKS_PER_SECOND);
 
     loop {
-        // Ball movement x axis
-        if ball_displacement[0] == -100 || ball_displacement[0] == 100 {
-            ball_displacement_x_increment = !ball_displacement_x_increment;
-        }
-
-        if ball_displacement_x_increment {
-            ball_displacement[0] += displacement_x_constant;
-        }
-        if !ball_displacement_x_increment {
-            ball_displacement[0] -= displacement_x_constant;
-        }
-        // Ball Movement y axis
-        if ball_displacement[1] == wall_y_top || ball_displacement[1] == wall_y_bottom {
-            ball_displacement_y_increment = !ball_displacement_y_increment;
-        }
-
-        if ball_displacement_y_increment {
-            ball_displacement[1] += displacement_y_constant;
-        }
-        if !ball_displacement_y_increment {
-            ball_displacement[1] -= displacement_y_constant;
-        }
-
-        // Point Scoring
-        if ball_displacement[0] == -100 {
-            score_player2 += 1;
-            ball_displacement = [0, rng.gen_range(-97, 97)];
-        }
-        if ball_displacement[0] == 100 {
-            score_player1 += 1;
-            ball_displacement = [0, rng.gen_range(-97, 97)];
-        }
-        //
-
-        // Auto Move Player
-        if single_player {
-            if player_2_displacement == 150 || player_2_displacement == 0 {
-                displacement_increment = !displacement_increment;
-            }
-            if displacement_increment {
-                player_2_displacement += displacement_x_constant;
+        for _ in 0..timestep.ticks() {
+            // Only a game being played moves, everything else stands still
+            if screen != Screen::Playing {
+                screen = screen.tick();
+                continue;
             }
-            if !displacement_increment {
-                player_2_displacement -= displacement_x_constant;
-            }
-        }
-        // Smooth Paddle Movement
-        if player_1_displacement_velocity != 0 {
-            if player_1_displacement_velocity.is_positive() {
-                player_1_displacement += player_1_displacement_velocity;
-                player_1_displacement_velocity -= 1;
-                if player_1_displacement > 150 {
-                    player_1_displacement = 150;
+            if let Some(replay) = playback.as_mut() {
+                // Replays press the recorded keys instead of the keyboard and computer
+                match replay.next_inputs() {
+                    Some(recorded) => inputs = recorded,
+                    None => {
+                        playback = None;
+                        screen = Screen::Title;
+                        continue;
+                    }
                 }
-            }
-            if player_1_displacement_velocity.is_negative() {
-                player_1_displacement += player_1_displacement_velocity;
-                player_1_displacement_velocity += 1;
-                if player_1_displacement < 0 {
-                    player_1_displacement = 0;
+            } else {
+                // Computer players replace any keys pressed for their paddle
+                for cpu in cpu_players.iter_mut() {
+                    *inputs.player_mut(cpu.player()) = cpu.think(&game, &mut cpu_rng);
                 }
             }
-        }
-        if player_2_displacement_velocity != 0 {
-            if player_2_displacement_velocity.is_positive() {
-                player_2_displacement += player_2_displacement_velocity;
-                player_2_displacement_velocity -= 1;
-                if player_2_displacement > 150 {
-                    player_2_displacement = 150;
-                }
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record(&inputs);
             }
-            if player_2_displacement_velocity.is_negative() {
-                player_2_displacement += player_2_displacement_velocity;
-                player_2_displacement_velocity += 1;
-                if player_2_displacement < 0 {
-                    player_2_displacement = 0;
+            for event in game.step(&inputs) {
+                match event {
+                    GameEvent::MatchWon(winner) => {
+                        let delay = game.config().rules.post_match_delay;
+                        screen = Screen::game_over(Some(winner), delay);
+                        save_stats(&game, options.stats.as_deref());
+                    }
+                    GameEvent::MatchLost => {
+                        screen = Screen::game_over(None, game.config().rules.post_match_delay);
+                        save_stats(&game, options.stats.as_deref());
+                    }
+                    GameEvent::PowerUpCollected(player, kind) => {
+                        let text = format!("Player {}: {}", player.number(), kind.name());
+                        announcement = Some((text, 2 * TICKS_PER_SECOND));
+                    }
+                    GameEvent::Eliminated(player) => {
+                        let text = format!("{} Out!", game.layout().name(player));
+                        announcement = Some((text, 2 * TICKS_PER_SECOND));
+                    }
+                    GameEvent::SetWon(player) => {
+                        let text = format!("{} Takes the Set!", game.layout().name(player));
+                        announcement = Some((text, 2 * TICKS_PER_SECOND));
+                    }
+                    GameEvent::SuddenDeath => {
+                        announcement = Some(("Sudden Death!".to_string(), 2 * TICKS_PER_SECOND));
+                    }
+                    GameEvent::StreakEnded(streak) if records_changed => {
+                        save_records(&records);
+                        records_changed = false;
+                        let text = format!("New Best: {}!", streak);
+                        announcement = Some((text, 2 * TICKS_PER_SECOND));
+                    }
+                    _ => (),
                 }
             }
-        }
-
-        let paddle_surface_player1: [f32; 2] = [-1.0, -0.5];
-        let paddle_surface_player2: [f32; 2] = [1.0, 0.5];
-        let paddle_surface_position_player1: [f32; 2] = [
-            paddle_surface_player1[0] + player_1_displacement as f32 / 100.0,
-            paddle_surface_player1[1] + player_1_displacement as f32 / 100.0,
-        ];
-        let paddle_surface_position_player2: [f32; 2] = [
-            paddle_surface_player2[0] - player_2_displacement as f32 / 100.0,
-            paddle_surface_player2[1] - player_2_displacement as f32 / 100.0,
-        ];
-        //Ball Bouncing off Paddle Logic
-        if ball_displacement[0] == paddle_y_player1 {
-            if ball_displacement[1] as f32 / 100.0 >= paddle_surface_position_player1[0]
-                && ball_displacement[1] as f32 / 100.0 <= paddle_surface_position_player1[1]
+            // Practice streaks count as soon as they beat the best, replays don't count
+            if game.layout() == Layout::Solo
+                && playback.is_none()
+                && records.record_streak(game.score.streak)
             {
-                ball_displacement_x_increment = !ball_displacement_x_increment;
-            }
-        }
-        if ball_displacement[0] == paddle_y_player2 {
-            if ball_displacement[1] as f32 / 100.0 >= paddle_surface_position_player2[1]
-                && ball_displacement[1] as f32 / 100.0 <= paddle_surface_position_player2[0]
-            {
-                ball_displacement_x_increment = !ball_displacement_x_increment;
+                records_changed = true;
             }
+            announcement = match announcement.take() {
+                Some((text, ticks)) if ticks > 1 => Some((text, ticks - 1)),
+                _ => None,
+            };
+            inputs.clear();
         }
-        //
-        // GPU Push Constants
-        let pc_player1 = vs_player1::ty::Displacement {
-            displacement: player_1_displacement as f32 / 100.0,
-        };
+        let alpha = timestep.alpha();
+        // Every ball is drawn from the same vertices, moved by its own push constant
+        let pc_balls: Vec<_> = game
+            .balls
+            .iter()
+            .map(|ball| {
+                let ball_displacement = ball.interpolate(alpha);
+                vs_ball::ty::BallPosition {
+                    projection,
+                    vector: ball_displacement,
+                    angle: ball.interpolate_angle(alpha),
+                }
+            })
+            .collect();
 
-        let pc_player2 = vs_player2::ty::Displacement {
-            displacement: -player_2_displacement as f32 / 100.0,
-        };
+        // Every paddle still in play, drawn where it starts and moved by a push constant.
+        // Power-ups and the arena change their size.
+        let paddles: Vec<_> = game
+            .active_players()
+            .into_iter()
+            .map(|player| {
+                #[derive(Default, Debug, Clone)]
+                struct Vertex {
+                    position: [f32; 2],
+                    color: [f32; 3],
+                }
 
-        let pc_ball = vs_ball::ty::BallPosition {
-            vector: [
-                ball_displacement[0] as f32 / 100.0,
-                ball_displacement[1] as f32 / 100.0,
-            ],
-        };
+                vulkano::impl_vertex!(Vertex, position, color);
 
-        if pc_ball.vector[0] < -1.0 {
-            println!("{}", pc_ball.vector[0]);
-        }
+                let rest = game.paddle_rest_rect(player);
+                let colors = [
+                    [1.0, 1.0, 1.0],
+                    [1.0, 1.0, 1.0],
+                    [0.0, 1.0, 1.0],
+                    [1.0, 1.0, 1.0],
+                    [1.0, 1.0, 1.0],
+                    [0.0, 1.0, 1.0],
+                ];
+                let vertex_buffer = CpuAccessibleBuffer::from_iter(
+                    device.clone(),
+                    BufferUsage::all(),
+                    rectangle(rest.min, rest.max)
+                        .into_iter()
+                        .zip(colors.iter())
+                        .map(|(corner, &color)| Vertex {
+                            position: corner,
+                            color,
+                        })
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                )
+                .unwrap();
+                // GPU Push Constants
+                let offset = game.paddle_offset(player, alpha);
+                let pc_paddle = vs_paddle::ty::PaddleOffset { projection, offset };
+                (vertex_buffer, pc_paddle)
+            })
+            .collect();
 
-        let vertex_buffer_player1 = {
+        let vertex_buffer_net = {
             #[derive(Default, Debug, Clone)]
             struct Vertex {
                 position: [f32; 2],
@@ -440,32 +470,35 @@ fn main() {
 
             vulkano::impl_vertex!(Vertex, position, color);
 
+            // Down the middle of the court
+            let half_height = game.arena().half_height;
+
             CpuAccessibleBuffer::from_iter(
                 device.clone(),
                 BufferUsage::all(),
                 [
                     Vertex {
-                        position: [-0.9, -1.0],
+                        position: [-0.5, -half_height],
                         color: [1.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [-0.8, paddle_surface_player1[0]],
+                        position: [0.5, -half_height],
                         color: [1.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [-0.9, paddle_surface_player1[1]],
+                        position: [-0.5, half_height],
                         color: [0.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [-0.8, -1.0],
+                        position: [-0.5, half_height],
                         color: [1.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [-0.8, -0.5],
+                        position: [0.5, half_height],
                         color: [1.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [-0.9, -0.5],
+                        position: [0.5, -half_height],
                         color: [0.0, 1.0, 1.0],
                     },
                 ]
@@ -475,51 +508,8 @@ fn main() {
             .unwrap()
         };
 
-        let vertex_buffer_player2 = {
-            #[derive(Default, Debug, Clone)]
-            struct Vertex {
-                position: [f32; 2],
-                colour: [f32; 3],
-            }
-
-            vulkano::impl_vertex!(Vertex, position, colour);
-
-            CpuAccessibleBuffer::from_iter(
-                device.clone(),
-                BufferUsage::all(),
-                [
-                    Vertex {
-                        position: [0.9, 1.0],
-                        colour: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.8, paddle_surface_player2[0]],
-                        colour: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.9, 0.5],
-                        colour: [0.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.8, 1.0],
-                        colour: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.8, paddle_surface_player2[1]],
-                        colour: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.9, 0.5],
-                        colour: [0.0, 1.0, 1.0],
-                    },
-                ]
-                .iter()
-                .cloned(),
-            )
-            .unwrap()
-        };
-
-        let vertex_buffer_net = {
+        // Walls, obstacles and bricks are drawn in the same way as the net
+        let vertex_buffer_arena = {
             #[derive(Default, Debug, Clone)]
             struct Vertex {
                 position: [f32; 2],
@@ -528,39 +518,27 @@ fn main() {
 
             vulkano::impl_vertex!(Vertex, position, color);
 
-            CpuAccessibleBuffer::from_iter(
-                device.clone(),
-                BufferUsage::all(),
-                [
-                    Vertex {
-                        position: [-0.005, -1.0],
-                        color: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.005, -1.0],
-                        color: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [-0.005, 1.0],
-                        color: [0.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [-0.005, 1.0],
-                        color: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.005, 1.0],
-                        color: [1.0, 1.0, 1.0],
-                    },
-                    Vertex {
-                        position: [0.005, -1.0],
-                        color: [0.0, 1.0, 1.0],
-                    },
-                ]
-                .iter()
-                .cloned(),
-            )
-            .unwrap()
+            let vertices: Vec<Vertex> = game
+                .walls()
+                .into_iter()
+                .chain(game.obstacle_rects(alpha))
+                .map(|rect| (rect, [0.6, 0.6, 0.6]))
+                .chain(
+                    game.bricks
+                        .iter()
+                        .map(|brick| (brick.rect, brick_color(brick))),
+                )
+                .flat_map(|(rect, color)| {
+                    rectangle(rect.min, rect.max)
+                        .into_iter()
+                        .map(move |corner| Vertex {
+                            position: corner,
+                            color,
+                        })
+                })
+                .collect();
+            CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(), vertices.into_iter())
+                .unwrap()
         };
 
         let vertex_buffer_ball = {
@@ -577,27 +555,27 @@ fn main() {
                 BufferUsage::all(),
                 [
                     Vertex {
-                        position: [-0.03, -0.03],
+                        position: [-BALL_HALF_SIZE, -BALL_HALF_SIZE],
                         color: [1.0, 0.0, 1.0],
                     },
                     Vertex {
-                        position: [0.03, -0.03],
+                        position: [BALL_HALF_SIZE, -BALL_HALF_SIZE],
                         color: [1.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [-0.03, 0.03],
+                        position: [-BALL_HALF_SIZE, BALL_HALF_SIZE],
                         color: [0.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [-0.03, 0.03],
+                        position: [-BALL_HALF_SIZE, BALL_HALF_SIZE],
                         color: [1.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [0.03, 0.03],
+                        position: [BALL_HALF_SIZE, BALL_HALF_SIZE],
                         color: [1.0, 1.0, 1.0],
                     },
                     Vertex {
-                        position: [0.03, -0.03],
+                        position: [BALL_HALF_SIZE, -BALL_HALF_SIZE],
                         color: [0.0, 1.0, 1.0],
                     },
                 ]
@@ -607,47 +585,183 @@ fn main() {
             .unwrap()
         };
 
+        // Each power-up on the court is drawn with its own sprite
+        let power_up_sprites: Vec<_> = game
+            .power_ups
+            .iter()
+            .map(|power_up| {
+                #[derive(Default, Debug, Clone)]
+                struct Vertex {
+                    position: [f32; 2],
+                    color: [f32; 3],
+                }
+
+                vulkano::impl_vertex!(Vertex, position, color);
+
+                let (color, triangles) = power_up_sprite(power_up.kind);
+                let vertex_buffer = CpuAccessibleBuffer::from_iter(
+                    device.clone(),
+                    BufferUsage::all(),
+                    triangles.into_iter().map(|corner| Vertex {
+                        position: corner,
+                        color,
+                    }),
+                )
+                .unwrap();
+                let pc_powerup = vs_powerup::ty::PowerUpPosition {
+                    projection,
+                    vector: power_up.position,
+                };
+                (vertex_buffer, pc_powerup)
+            })
+            .collect();
+
         if x > width as f32 {
             x = 0.0;
         } else {
             x += 0.4;
         }
-        draw_text.queue_text(
-            630.0,
-            200.0,
-            190.0,
-            [0.0, 1.0, 1.0, 1.0],
-            &score_player1.to_string(),
-        );
-        draw_text.queue_text(
-            800.0,
-            200.0,
-            190.0,
-            [0.0, 1.0, 1.0, 1.0],
-            &score_player2.to_string(),
-        );
-        let score_to_win = 9;
-        // Player Wins, Reset Score and let them know they won for a bit
-        if (score_player1 == score_to_win || score_player2 == score_to_win) && !time_is_set {
-            theres_a_winner = true;
-            time = Instant::now();
-            time_is_set = true;
+        if game.layout() == Layout::Solo {
+            // Returns so far and the best there's ever been
+            draw_text.queue_text(
+                700.0,
+                200.0,
+                190.0,
+                [0.0, 1.0, 1.0, 1.0],
+                &game.score.streak.to_string(),
+            );
+            let best = records.best_streak.max(game.score.streak);
+            draw_text.queue_text(
+                700.0,
+                80.0,
+                70.0,
+                [0.0, 1.0, 1.0, 1.0],
+                &format!("Best {}", best),
+            );
+        } else if game.layout() == Layout::Bricks {
+            // Bricks hit so far and balls left to lose
+            draw_text.queue_text(
+                400.0,
+                200.0,
+                190.0,
+                [0.0, 1.0, 1.0, 1.0],
+                &game.score.points(Player::One).to_string(),
+            );
+            let lives = game.score.lives_left(Player::One, &game.config().rules);
+            draw_text.queue_text(
+                400.0,
+                80.0,
+                70.0,
+                [0.0, 1.0, 1.0, 1.0],
+                &format!("Lives {}", lives),
+            );
+        } else if game.layout().has_two_ends() {
+            draw_text.queue_text(
+                630.0,
+                200.0,
+                190.0,
+                [0.0, 1.0, 1.0, 1.0],
+                &game.score.points(Player::One).to_string(),
+            );
+            draw_text.queue_text(
+                800.0,
+                200.0,
+                190.0,
+                [0.0, 1.0, 1.0, 1.0],
+                &game.score.points(Player::Two).to_string(),
+            );
+        } else {
+            // Lives left, next to each player's edge
+            for &player in game.players() {
+                let (x, y) = match game.edge(player) {
+                    Edge::Left => (120.0, 560.0),
+                    Edge::Right => (1650.0, 560.0),
+                    Edge::Top => (880.0, 180.0),
+                    Edge::Bottom => (880.0, 980.0),
+                };
+                let lives = game.score.lives_left(player, &game.config().rules);
+                let text = if lives == 0 {
+                    format!("P{} out", player.number())
+                } else {
+                    format!("P{} {}", player.number(), lives)
+                };
+                draw_text.queue_text(x, y, 70.0, [0.0, 1.0, 1.0, 1.0], &text);
+            }
+        }
+        // Time left in a timed match, rounded up to the second
+        if let Some(time_left) = game.time_left() {
+            let text = if game.is_sudden_death() {
+                "Sudden Death".to_string()
+            } else {
+                let seconds = time_left.ceil() as u32;
+                format!("{}:{:02}", seconds / 60, seconds % 60)
+            };
+            draw_text.queue_text(40.0, 80.0, 70.0, [0.0, 1.0, 1.0, 1.0], &text);
+        }
+        // Sets won so far, when there is more than one and they're played to points
+        let rules = &game.config().rules;
+        if game.layout().has_two_ends() && rules.sets > 1 && !rules.is_timed() {
+            draw_text.queue_text(
+                700.0,
+                80.0,
+                70.0,
+                [0.0, 1.0, 1.0, 1.0],
+                &format!(
+                    "Sets {} - {}",
+                    game.score.sets(Player::One),
+                    game.score.sets(Player::Two)
+                ),
+            );
         }
-        if theres_a_winner {
-            if score_player1 == score_to_win {
-                draw_text.queue_text(800.0, 400.0, 150.0, [0.0, 1.0, 1.0, 1.0], "Player 1 Wins!");
+        match screen {
+            Screen::Title => {
+                draw_text.queue_text(560.0, 400.0, 250.0, [0.0, 1.0, 1.0, 1.0], "J-Pong");
+                let hint = menu_error.as_deref().unwrap_or("Enter to start");
+                queue_menu(&mut draw_text, &menu, hint);
             }
-            if score_player2 == score_to_win {
-                draw_text.queue_text(80.0, 400.0, 150.0, [0.0, 1.0, 1.0, 1.0], "Player 2 Wins!");
+            Screen::Playing => {
+                if let Some(ticks) = game.serve_countdown {
+                    draw_text.queue_text(
+                        760.0,
+                        650.0,
+                        190.0,
+                        [1.0, 1.0, 1.0, 1.0],
+                        &countdown_text(ticks),
+                    );
+                }
+                if let Some((text, _)) = &announcement {
+                    draw_text.queue_text(200.0, 950.0, 80.0, [1.0, 1.0, 0.0, 1.0], text);
+                }
+            }
+            Screen::Paused => {
+                draw_text.queue_text(600.0, 400.0, 190.0, [1.0, 1.0, 1.0, 1.0], "Paused");
+                let hint = menu_error
+                    .as_deref()
+                    .unwrap_or("Enter to resume, Q to quit");
+                queue_menu(&mut draw_text, &menu, hint);
+            }
+            // Let the winner know they won for a bit, across the court from an end player
+            Screen::GameOver {
+                winner: Some(winner),
+                ..
+            } => {
+                let x = match game.edge(winner) {
+                    Edge::Left => 800.0,
+                    Edge::Right => 80.0,
+                    Edge::Top | Edge::Bottom => 440.0,
+                };
+                let text = format!("{} Wins!", game.layout().name(winner));
+                draw_text.queue_text(x, 400.0, 150.0, [0.0, 1.0, 1.0, 1.0], &text);
+            }
+            Screen::GameOver { winner: None, .. } => {
+                draw_text.queue_text(440.0, 400.0, 150.0, [1.0, 0.0, 0.0, 1.0], "Game Over");
             }
-            ball_displacement[0] = 0;
-            ball_displacement[1] = 0;
-
-            if time.elapsed() > Duration::from_secs(3) {
-                score_player1 = 0;
-                score_player2 = 0;
-                theres_a_winner = false;
-                time_is_set = false;
+        }
+        // How the match went, under whoever won it
+        if let Screen::GameOver { .. } = screen {
+            for (i, line) in stats_lines(&game).iter().enumerate() {
+                let y = 550.0 + i as f32 * 60.0;
+                draw_text.queue_text(440.0, y, 50.0, [1.0, 1.0, 1.0, 1.0], line);
             }
         }
         // Frees no longer needed resources
@@ -669,8 +783,14 @@ fn main() {
             };
 
             swapchain = new_swapchain;
-            framebuffers =
-                window_size_dependent_setup(&new_images, render_pass.clone(), &mut dynamic_state);
+            let (new_framebuffers, new_projection) = window_size_dependent_setup(
+                &new_images,
+                render_pass.clone(),
+                &mut dynamic_state,
+                &camera,
+            );
+            framebuffers = new_framebuffers;
+            projection = new_projection;
             draw_text = DrawText::new(
                 device.clone(),
                 queue.clone(),
@@ -694,37 +814,58 @@ fn main() {
         let clear_values = vec![[0.0, 0.0, 0.0, 0.0].into()];
 
         // In order to draw, we have to build a *command buffer*.
-        let command_buffer =
+        let mut command_buffer =
             AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family())
                 .unwrap()
                 // Before we can draw, we have to *enter a render pass*.
                 .begin_render_pass(framebuffers[image_num].clone(), false, clear_values)
-                .unwrap()
-                // The subpasses of the render pass are each of these
+                .unwrap();
+        // The subpasses of the render pass are each of these
+        let pc_net = vs_net::ty::Projection { projection };
+        if game.arena().net {
+            command_buffer = command_buffer
                 .draw(
                     pipeline_net.clone(),
                     &dynamic_state,
                     vertex_buffer_net.clone(),
                     (),
-                    (),
+                    pc_net,
                 )
-                .unwrap()
+                .unwrap();
+        }
+        command_buffer = command_buffer
+            .draw(
+                pipeline_net.clone(),
+                &dynamic_state,
+                vertex_buffer_arena.clone(),
+                (),
+                pc_net,
+            )
+            .unwrap();
+        for (vertex_buffer_paddle, pc_paddle) in paddles {
+            command_buffer = command_buffer
                 .draw(
-                    pipeline.clone(),
+                    pipeline_paddle.clone(),
                     &dynamic_state,
-                    vertex_buffer_player1.clone(),
+                    vertex_buffer_paddle,
                     (),
-                    pc_player1,
+                    pc_paddle,
                 )
-                .unwrap()
+                .unwrap();
+        }
+        for (vertex_buffer_powerup, pc_powerup) in power_up_sprites {
+            command_buffer = command_buffer
                 .draw(
-                    pipeline_player2.clone(),
+                    pipeline_powerup.clone(),
                     &dynamic_state,
-                    vertex_buffer_player2.clone(),
+                    vertex_buffer_powerup,
                     (),
-                    pc_player2,
+                    pc_powerup,
                 )
-                .unwrap()
+                .unwrap();
+        }
+        for pc_ball in pc_balls {
+            command_buffer = command_buffer
                 .draw(
                     pipeline_ball.clone(),
                     &dynamic_state,
@@ -733,6 +874,7 @@ fn main() {
                     pc_ball,
                 )
                 .unwrap();
+        }
         let command_buffer = command_buffer
             .end_render_pass()
             .unwrap()
@@ -775,134 +917,90 @@ fn main() {
                     WindowEvent::KeyboardInput {
                         input:
                             KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::W),
-                                ..
-                            },
-                        ..
-                    },
-                ..
-            } => {
-                //player_1_displacement = displace_player("Up".to_owned(), player_1_displacement);
-                player_1_displacement_velocity -= 2;
-            }
-            Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Released,
-                                virtual_keycode: Some(VirtualKeyCode::W),
-                                ..
-                            },
-                        ..
-                    },
-                ..
-            } => {
-                player_1_displacement_velocity = -4;
-            }
-            Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::S),
-                                ..
-                            },
-                        ..
-                    },
-                ..
-            } => {
-                //player_1_displacement = displace_player("Down".to_owned(), player_1_displacement);
-                player_1_displacement_velocity += 2;
-            }
-            Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Released,
-                                virtual_keycode: Some(VirtualKeyCode::S),
-                                ..
-                            },
-                        ..
-                    },
-                ..
-            } => {
-                player_1_displacement_velocity = 4;
-            }
-            Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Up),
-                                ..
-                            },
-                        ..
-                    },
-                ..
-            } => {
-                if !single_player {
-                    //player_2_displacement =
-                    //displace_player("Down".to_owned(), player_2_displacement);
-                    player_2_displacement_velocity += 2;
-                }
-            }
-
-            Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Released,
-                                virtual_keycode: Some(VirtualKeyCode::Up),
+                                state,
+                                virtual_keycode: Some(key),
                                 ..
                             },
                         ..
                     },
                 ..
             } => {
-                if !single_player {
-                    player_2_displacement_velocity = 4;
+                // Keys for a paddle the computer or a replay is playing do nothing
+                let paddle_key = paddle_control(key, state)
+                    .filter(|&(player, _)| !mode.is_cpu(player) && playback.is_none());
+                match screen {
+                    Screen::Title | Screen::Paused if state == ElementState::Pressed => {
+                        if let Some(menu_key) = menu_key(key) {
+                            menu_error = None;
+                            if menu.handle(menu_key) {
+                                // Some modes don't fit in some arenas
+                                menu_error = menu.mode.layout().validate(&options.config).err();
+                                if menu_error.is_none() {
+                                    // A paddle handed over from the computer lets go of
+                                    // whatever the computer was holding
+                                    for &player in game.players() {
+                                        if mode.is_cpu(player) && !menu.mode.is_cpu(player) {
+                                            release_paddle(&mut inputs, player);
+                                        }
+                                    }
+                                    mode = menu.mode;
+                                    cpu_players = mode.cpu_players(menu.difficulty);
+                                    // Changing where everyone plays from the pause menu starts over
+                                    new_match =
+                                        screen == Screen::Title || mode.layout() != game.layout();
+                                    screen = Screen::Playing;
+                                }
+                            }
+                        } else if screen == Screen::Paused {
+                            match key {
+                                VirtualKeyCode::Escape | VirtualKeyCode::P => {
+                                    screen = Screen::Playing
+                                }
+                                VirtualKeyCode::Q => screen = Screen::Title,
+                                _ => (),
+                            }
+                        } else if key == VirtualKeyCode::Escape {
+                            done = true;
+                        }
+                    }
+                    Screen::Playing => {
+                        if let Some((player, action)) = paddle_key {
+                            inputs.push(player, action);
+                        } else if state == ElementState::Pressed {
+                            match key {
+                                VirtualKeyCode::Escape
+                                | VirtualKeyCode::P
+                                | VirtualKeyCode::Tab => screen = screen.toggle_pause(),
+                                VirtualKeyCode::Space => inputs.serve = true,
+                                _ => (),
+                            }
+                        }
+                    }
+                    Screen::GameOver { .. } if state == ElementState::Pressed => {
+                        if let VirtualKeyCode::Return | VirtualKeyCode::Escape = key {
+                            screen = Screen::Title;
+                        }
+                    }
+                    // Letting go of a paddle key still counts off the court,
+                    // so the paddle isn't left held down after a pause
+                    _ => {
+                        if let Some((player, action)) = paddle_key {
+                            inputs.push(player, action);
+                        }
+                    }
                 }
             }
-
+            // Keys let go of in another window never arrive here, so let go of them all
             Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Down),
-                                ..
-                            },
-                        ..
-                    },
+                event: WindowEvent::Focused(false),
                 ..
             } => {
-                if !single_player {
-                    //player_2_displacement = displace_player("Up".to_owned(), player_2_displacement);
-                    player_2_displacement_velocity -= 2;
-                }
-            }
-            Event::WindowEvent {
-                event:
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Released,
-                                virtual_keycode: Some(VirtualKeyCode::Down),
-                                ..
-                            },
-                        ..
-                    },
-                ..
-            } => {
-                if !single_player {
-                    player_2_displacement_velocity = -4;
+                if playback.is_none() {
+                    for &player in game.players() {
+                        if !mode.is_cpu(player) {
+                            release_paddle(&mut inputs, player);
+                        }
+                    }
                 }
             }
             Event::WindowEvent {
@@ -911,31 +1009,259 @@ fn main() {
             } => recreate_swapchain = true,
             _ => (),
         });
+        // A match is over once it's back to the title, save it if it's being recorded
+        if screen == Screen::Title || done {
+            save_replay(recorder.take());
+            // and keep a practice streak that was the best yet when it was stopped
+            if records_changed {
+                save_records(&records);
+                records_changed = false;
+            }
+        }
+        if new_match {
+            new_match = false;
+            game = GameState::new(options.config.clone(), mode.layout(), new_seed());
+            cpu_rng = StdRng::seed_from_u64(game.seed());
+            playback = None;
+            // A layout picked from the pause menu starts over without going back to the title
+            save_replay(recorder.take());
+            recorder = options.record.clone().map(|path| {
+                Recorder::new(path, game.seed(), game.layout(), options.config.clone())
+            });
+        }
         if done {
             return;
         }
     }
 }
 
+// Shown while the ball waits to be served, counting down the seconds
+fn countdown_text(ticks: u32) -> String {
+    ((ticks + TICKS_PER_SECOND - 1) / TICKS_PER_SECOND)
+        .max(1)
+        .to_string()
+}
+
+// What each power-up looks like, a colour and triangles around its centre in court units
+fn power_up_sprite(kind: PowerUpKind) -> ([f32; 3], Vec<[f32; 2]>) {
+    let size = POWER_UP_HALF_SIZE;
+    let bar = size / 3.0;
+    match kind {
+        // A plus
+        PowerUpKind::GrowPaddle => (
+            [0.0, 1.0, 0.0],
+            [
+                rectangle([-bar, -size], [bar, size]),
+                rectangle([-size, -bar], [size, bar]),
+            ]
+            .concat(),
+        ),
+        // A minus
+        PowerUpKind::ShrinkPaddle => ([1.0, 0.0, 0.0], rectangle([-size, -bar], [size, bar])),
+        // Two arrow heads
+        PowerUpKind::FastBall => (
+            [1.0, 1.0, 0.0],
+            vec![
+                [-size, -size],
+                [0.0, 0.0],
+                [-size, size],
+                [0.0, -size],
+                [size, 0.0],
+                [0.0, size],
+            ],
+        ),
+        // A block
+        PowerUpKind::SlowBall => (
+            [0.3, 0.5, 1.0],
+            rectangle([-size * 0.8, -size * 0.8], [size * 0.8, size * 0.8]),
+        ),
+        // An hourglass
+        PowerUpKind::InvertControls => (
+            [1.0, 0.0, 1.0],
+            vec![
+                [-size, size],
+                [size, size],
+                [0.0, 0.0],
+                [-size, -size],
+                [size, -size],
+                [0.0, 0.0],
+            ],
+        ),
+        // Two little balls
+        PowerUpKind::SplitBall => (
+            [1.0, 1.0, 1.0],
+            [
+                rectangle([-size, -bar], [-bar, bar]),
+                rectangle([bar, -bar], [size, bar]),
+            ]
+            .concat(),
+        ),
+    }
+}
+
+// Two triangles covering a rectangle
+fn rectangle(min: [f32; 2], max: [f32; 2]) -> Vec<[f32; 2]> {
+    vec![
+        [min[0], min[1]],
+        [max[0], min[1]],
+        [min[0], max[1]],
+        [min[0], max[1]],
+        [max[0], max[1]],
+        [max[0], min[1]],
+    ]
+}
+
+// Bricks that drop power-ups stand out, the rest get darker the more hits they take to break
+fn brick_color(brick: &Brick) -> [f32; 3] {
+    if brick.drops_power_up {
+        return [1.0, 0.8, 0.0];
+    }
+    let shade = 1.0 / brick.hits_left as f32;
+    [0.2 + 0.8 * shade, 0.3 * shade, 0.5]
+}
+
+fn save_records(records: &Records) {
+    if let Err(message) = records.save(Path::new(RECORDS_FILE)) {
+        eprintln!("{}", message);
+    }
+}
+
+fn save_replay(recorder: Option<Recorder>) {
+    if let Some(recorder) = recorder {
+        if let Err(message) = recorder.save() {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+fn save_stats(game: &GameState, path: Option<&Path>) {
+    if let Some(path) = path {
+        if let Err(message) = game.stats.save(path) {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+// The match's stats a line at a time, for the game over screen
+fn stats_lines(game: &GameState) -> Vec<String> {
+    let stats = &game.stats;
+    let hits: Vec<_> = game
+        .players()
+        .iter()
+        .map(|player| format!("P{} {}", player.number(), stats.paddle_hits[player.index()]))
+        .collect();
+    let mut lines = vec![
+        format!(
+            "Rallies {}, longest {} hits, {:.1}s on average",
+            stats.rallies, stats.longest_rally, stats.average_rally_seconds
+        ),
+        format!("Hits: {}", hits.join(", ")),
+    ];
+    // Points are only won off another end, and go to the back of each team
+    let layout = game.layout();
+    if layout.has_two_ends() {
+        let (one, two) = (Player::One, Player::Two);
+        lines.push(format!(
+            "Won on serve: {} {}, {} {}",
+            layout.name(one),
+            stats.points_on_serve[one.index()],
+            layout.name(two),
+            stats.points_on_serve[two.index()]
+        ));
+        lines.push(format!(
+            "Won on return: {} {}, {} {}",
+            layout.name(one),
+            stats.points_on_return[one.index()],
+            layout.name(two),
+            stats.points_on_return[two.index()]
+        ));
+    }
+    lines
+}
+
+// The menu's choices with a hint for what to do next below them
+fn queue_menu(draw_text: &mut DrawText, menu: &Menu, hint: &str) {
+    for (i, line) in menu.lines().iter().enumerate() {
+        draw_text.queue_text(
+            200.0,
+            650.0 + 90.0 * i as f32,
+            80.0,
+            [1.0, 1.0, 1.0, 1.0],
+            line,
+        );
+    }
+    draw_text.queue_text(200.0, 900.0, 60.0, [0.0, 1.0, 1.0, 1.0], hint);
+}
+
+// Keyboard controls for each paddle
+fn paddle_control(key: VirtualKeyCode, state: ElementState) -> Option<(Player, PaddleAction)> {
+    let (player, direction) = match key {
+        VirtualKeyCode::W => (Player::One, Direction::Up),
+        VirtualKeyCode::S => (Player::One, Direction::Down),
+        VirtualKeyCode::Up => (Player::Two, Direction::Up),
+        VirtualKeyCode::Down => (Player::Two, Direction::Down),
+        // The top and bottom paddles move left with Up and right with Down
+        VirtualKeyCode::T => (Player::Three, Direction::Up),
+        VirtualKeyCode::Y => (Player::Three, Direction::Down),
+        VirtualKeyCode::N => (Player::Four, Direction::Up),
+        VirtualKeyCode::M => (Player::Four, Direction::Down),
+        _ => return None,
+    };
+    let action = match state {
+        ElementState::Pressed => PaddleAction::Press(direction),
+        ElementState::Released => PaddleAction::Release(direction),
+    };
+    Some((player, action))
+}
+
+// Let go of both of a paddle's keys
+fn release_paddle(inputs: &mut Inputs, player: Player) {
+    inputs.push(player, PaddleAction::Release(Direction::Up));
+    inputs.push(player, PaddleAction::Release(Direction::Down));
+}
+
+fn menu_key(key: VirtualKeyCode) -> Option<MenuKey> {
+    match key {
+        VirtualKeyCode::Up | VirtualKeyCode::W => Some(MenuKey::Up),
+        VirtualKeyCode::Down | VirtualKeyCode::S => Some(MenuKey::Down),
+        VirtualKeyCode::Left | VirtualKeyCode::A => Some(MenuKey::Left),
+        VirtualKeyCode::Right | VirtualKeyCode::D => Some(MenuKey::Right),
+        VirtualKeyCode::Return | VirtualKeyCode::Space | VirtualKeyCode::Tab => {
+            Some(MenuKey::Confirm)
+        }
+        _ => None,
+    }
+}
+
+// Framebuffers for the window's new size, and the projection from world units to the screen.
+// Drawing is cut off outside the camera's letterbox so the court keeps its shape.
 fn window_size_dependent_setup(
     images: &[Arc<SwapchainImage<Window>>],
     render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
     dynamic_state: &mut DynamicState,
-) -> Vec<Arc<dyn FramebufferAbstract + Send + Sync>> {
+    camera: &Camera,
+) -> (Vec<Arc<dyn FramebufferAbstract + Send + Sync>>, [f32; 4]) {
     let dimensions = images[0].dimensions();
     println!(
         "Dimensions: {}x{}",
         dimensions[0] as f32, dimensions[1] as f32
     );
 
+    let window = [dimensions[0] as f32, dimensions[1] as f32];
     let viewport = Viewport {
         origin: [0.0, 0.0],
-        dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+        dimensions: window,
         depth_range: 0.0..1.0,
     };
     dynamic_state.viewports = Some(vec![viewport]);
+    let (corner, size) = camera.letterbox(window);
+    let scissor = Scissor {
+        origin: [corner[0].round() as i32, corner[1].round() as i32],
+        dimensions: [size[0].round() as u32, size[1].round() as u32],
+    };
+    dynamic_state.scissors = Some(vec![scissor]);
 
-    images
+    let framebuffers = images
         .iter()
         .map(|image| {
             Arc::new(
@@ -946,5 +1272,6 @@ fn window_size_dependent_setup(
                     .unwrap(),
             ) as Arc<dyn FramebufferAbstract + Send + Sync>
         })
-        .collect::<Vec<_>>()
+        .collect::<Vec<_>>();
+    (framebuffers, camera.projection(window))
 }
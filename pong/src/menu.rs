@@ -0,0 +1,80 @@
+use crate::ai::Difficulty;
+use crate::mode::GameMode;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Item {
+    Mode,
+    Difficulty,
+}
+
+const ITEMS: [Item; 2] = [Item::Mode, Item::Difficulty];
+
+/// Menu for choosing who controls each paddle, shown on the title and pause screens
+#[derive(Clone, Debug)]
+pub struct Menu {
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    selected: usize,
+}
+
+impl Menu {
+    pub fn new(mode: GameMode, difficulty: Difficulty) -> Menu {
+        Menu {
+            mode,
+            difficulty,
+            selected: 0,
+        }
+    }
+
+    /// Returns true when the choice is confirmed
+    pub fn handle(&mut self, key: MenuKey) -> bool {
+        match key {
+            MenuKey::Up => self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len(),
+            MenuKey::Down => self.selected = (self.selected + 1) % ITEMS.len(),
+            MenuKey::Left => self.change(-1),
+            MenuKey::Right => self.change(1),
+            MenuKey::Confirm => return true,
+        }
+        false
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        ITEMS
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let cursor = if i == self.selected { ">" } else { " " };
+                match item {
+                    Item::Mode => format!("{} Mode: {}", cursor, self.mode.name()),
+                    Item::Difficulty => format!("{} CPU: {}", cursor, self.difficulty.name()),
+                }
+            })
+            .collect()
+    }
+
+    fn change(&mut self, step: isize) {
+        match ITEMS[self.selected] {
+            Item::Mode => self.mode = cycle(&GameMode::ALL, self.mode, step),
+            Item::Difficulty => self.difficulty = cycle(&Difficulty::ALL, self.difficulty, step),
+        }
+    }
+}
+
+// The option `step` places along from `current`, wrapping around at either end
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: isize) -> T {
+    let index = all
+        .iter()
+        .position(|&option| option == current)
+        .unwrap_or(0) as isize;
+    let len = all.len() as isize;
+    all[(index + step).rem_euclid(len) as usize]
+}
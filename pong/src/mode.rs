@@ -0,0 +1,98 @@
+use crate::ai::{CpuPlayer, Difficulty};
+use crate::game::{Layout, Player};
+
+/// Who is in control of each paddle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameMode {
+    TwoPlayer,
+    CpuPlayer1,
+    CpuPlayer2,
+    CpuVsCpu,
+    FourPlayer,
+    // Player 1 against three computers
+    CpuFourPlayer,
+    // Players 1 and 3 against 2 and 4
+    Doubles,
+    // Player 1 against a wall
+    Solo,
+    // Player 1 against a wall of bricks
+    Bricks,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 9] = [
+        GameMode::TwoPlayer,
+        GameMode::CpuPlayer1,
+        GameMode::CpuPlayer2,
+        GameMode::CpuVsCpu,
+        GameMode::FourPlayer,
+        GameMode::CpuFourPlayer,
+        GameMode::Doubles,
+        GameMode::Solo,
+        GameMode::Bricks,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::TwoPlayer => "2 Players",
+            GameMode::CpuPlayer1 => "CPU vs Player 2",
+            GameMode::CpuPlayer2 => "Player 1 vs CPU",
+            GameMode::CpuVsCpu => "CPU vs CPU",
+            GameMode::FourPlayer => "4 Players",
+            GameMode::CpuFourPlayer => "Player 1 vs 3 CPUs",
+            GameMode::Doubles => "2 vs 2",
+            GameMode::Solo => "Practice",
+            GameMode::Bricks => "Bricks",
+        }
+    }
+
+    // Name used on the command line
+    pub fn flag(self) -> &'static str {
+        match self {
+            GameMode::TwoPlayer => "2p",
+            GameMode::CpuPlayer1 => "cpu-p1",
+            GameMode::CpuPlayer2 => "cpu-p2",
+            GameMode::CpuVsCpu => "cpu-vs-cpu",
+            GameMode::FourPlayer => "4p",
+            GameMode::CpuFourPlayer => "4p-cpu",
+            GameMode::Doubles => "doubles",
+            GameMode::Solo => "solo",
+            GameMode::Bricks => "bricks",
+        }
+    }
+
+    pub fn parse(flag: &str) -> Option<GameMode> {
+        GameMode::ALL
+            .iter()
+            .cloned()
+            .find(|mode| mode.flag() == flag)
+    }
+
+    /// Where the paddles in play are
+    pub fn layout(self) -> Layout {
+        match self {
+            GameMode::FourPlayer | GameMode::CpuFourPlayer => Layout::FourWay,
+            GameMode::Doubles => Layout::Doubles,
+            GameMode::Solo => Layout::Solo,
+            GameMode::Bricks => Layout::Bricks,
+            _ => Layout::Singles,
+        }
+    }
+
+    pub fn is_cpu(self, player: Player) -> bool {
+        matches!(
+            (self, player),
+            (GameMode::CpuVsCpu, _)
+                | (GameMode::CpuPlayer1, Player::One)
+                | (GameMode::CpuPlayer2, Player::Two)
+        ) || (self == GameMode::CpuFourPlayer && player != Player::One)
+    }
+
+    pub fn cpu_players(self, difficulty: Difficulty) -> Vec<CpuPlayer> {
+        Player::ALL[..self.layout().players()]
+            .iter()
+            .filter(|&&player| self.is_cpu(player))
+            .map(|&player| CpuPlayer::new(player, difficulty))
+            .collect()
+    }
+}
@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::ai::Difficulty;
+use crate::arena::Arena;
+use crate::config::GameConfig;
+use crate::level::Level;
+use crate::mode::GameMode;
+use crate::rules::ServeTo;
+
+const USAGE: &str = "Usage: pong [OPTIONS]
+
+Options:
+    --mode <MODE>              2p, cpu-p1, cpu-p2, cpu-vs-cpu, 4p, 4p-cpu, doubles, solo or bricks [default: 2p]
+    --difficulty <DIFFICULTY>  easy, normal, hard or perfect [default: normal]
+    --config <FILE>            RON file with the game's tuning and match rules
+    --arena <FILE>             RON file with the court's size, goals and obstacles
+    --level <FILE>             RON file with the bricks to knock down in bricks mode
+    --points <N>               Points needed to win a set [default: 9]
+    --win-by <N>               How far ahead the winner of a set has to be [default: 1]
+    --sets <N>                 Play best of N sets [default: 1]
+    --post-match-delay <SECS>  How long to show the winner for [default: 3]
+    --serve-to <PLAYER>        loser, winner, alternate or random [default: loser]
+    --serve-delay <SECS>       Countdown before each serve [default: 3]
+    --lives <N>                Balls each player can let past in a 4 player or bricks game [default: 5]
+    --time-limit <SECS>        Play for a set time instead of to a score [default: 0, no limit]
+    --balls <N>                Most balls in play at once, more join long rallies [default: 1]
+    --power-ups                Power-ups appear on the court during rallies
+    --spin                     Moving paddles spin the ball and bend its path
+    --record <FILE>            Save each match played to a replay file
+    --replay <FILE>            Play back a replay file
+    --stats <FILE>             Save the stats of each match played to a JSON file
+    --seed <N>                 Seed for the game's randomness [default: random]
+    -h, --help                 Print this message";
+
+/// Settings chosen on the command line
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub config: GameConfig,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub stats: Option<PathBuf>,
+    pub seed: Option<u64>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            mode: GameMode::TwoPlayer,
+            difficulty: Difficulty::Normal,
+            config: GameConfig::default(),
+            record: None,
+            replay: None,
+            stats: None,
+            seed: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn from_args() -> Result<Options, String> {
+        Options::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        // The config file is read first so the rest of the flags can override it
+        let mut config_path = None;
+        let mut arena_path = None;
+        let mut level_path = None;
+        let mut points = None;
+        let mut win_by = None;
+        let mut sets = None;
+        let mut post_match_delay = None;
+        let mut serve_to = None;
+        let mut serve_delay = None;
+        let mut lives = None;
+        let mut time_limit = None;
+        let mut balls = None;
+        let mut enable_power_ups = None;
+        let mut enable_spin = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("{} needs a value\n\n{}", arg, USAGE))
+            };
+            match arg.as_str() {
+                "--mode" => {
+                    let value = value()?;
+                    options.mode = GameMode::parse(&value)
+                        .ok_or_else(|| format!("unknown mode {}\n\n{}", value, USAGE))?;
+                }
+                "--difficulty" => {
+                    let value = value()?;
+                    options.difficulty = Difficulty::parse(&value)
+                        .ok_or_else(|| format!("unknown difficulty {}\n\n{}", value, USAGE))?;
+                }
+                "--config" => config_path = Some(value()?),
+                "--arena" => arena_path = Some(value()?),
+                "--level" => level_path = Some(value()?),
+                "--points" => points = Some(number(&arg, value()?)?),
+                "--win-by" => win_by = Some(number(&arg, value()?)?),
+                "--sets" => sets = Some(number(&arg, value()?)?),
+                "--post-match-delay" => post_match_delay = Some(number(&arg, value()?)?),
+                "--serve-to" => {
+                    let value = value()?;
+                    serve_to = Some(
+                        ServeTo::parse(&value)
+                            .ok_or_else(|| format!("unknown serve {}\n\n{}", value, USAGE))?,
+                    );
+                }
+                "--serve-delay" => serve_delay = Some(number(&arg, value()?)?),
+                "--lives" => lives = Some(number(&arg, value()?)?),
+                "--time-limit" => time_limit = Some(number(&arg, value()?)?),
+                "--balls" => balls = Some(number(&arg, value()?)?),
+                "--power-ups" => enable_power_ups = Some(true),
+                "--spin" => enable_spin = Some(true),
+                "--record" => options.record = Some(PathBuf::from(value()?)),
+                "--replay" => options.replay = Some(PathBuf::from(value()?)),
+                "--stats" => options.stats = Some(PathBuf::from(value()?)),
+                "--seed" => options.seed = Some(number(&arg, value()?)?),
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                _ => return Err(format!("unknown argument {}\n\n{}", arg, USAGE)),
+            }
+        }
+
+        if let Some(path) = config_path {
+            options.config = GameConfig::load(Path::new(&path))?;
+        }
+        if let Some(path) = arena_path {
+            options.config.arena = Arena::load(Path::new(&path))?;
+        }
+        options.config.arena.validate()?;
+        if let Some(path) = level_path {
+            options.config.level = Level::load(Path::new(&path))?;
+        }
+        // Picking another mode from the menu checks it then
+        options.mode.layout().validate(&options.config)?;
+        options.config.paddle_movement.validate()?;
+        let rules = &mut options.config.rules;
+        rules.points_to_win = points.unwrap_or(rules.points_to_win);
+        rules.win_by = win_by.unwrap_or(rules.win_by);
+        rules.sets = sets.unwrap_or(rules.sets);
+        rules.post_match_delay = post_match_delay.unwrap_or(rules.post_match_delay);
+        rules.serve_to = serve_to.unwrap_or(rules.serve_to);
+        rules.serve_delay = serve_delay.unwrap_or(rules.serve_delay);
+        rules.lives = lives.unwrap_or(rules.lives);
+        rules.time_limit = time_limit.unwrap_or(rules.time_limit);
+        rules.validate()?;
+        let multiball = &mut options.config.multiball;
+        multiball.max_balls = balls.unwrap_or(multiball.max_balls);
+        multiball.validate()?;
+        let power_ups = &mut options.config.power_ups;
+        power_ups.enabled = enable_power_ups.unwrap_or(power_ups.enabled);
+        power_ups.validate()?;
+        let spin = &mut options.config.spin;
+        spin.enabled = enable_spin.unwrap_or(spin.enabled);
+        spin.validate()?;
+        if options.record.is_some() && options.replay.is_some() {
+            return Err(format!("can't record while playing a replay\n\n{}", USAGE));
+        }
+        Ok(options)
+    }
+}
+
+fn number<T: FromStr>(flag: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} needs a number, not {}\n\n{}", flag, value, USAGE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn level_only_has_to_fit_the_arena_in_bricks_mode() {
+        let small = concat!(env!("CARGO_MANIFEST_DIR"), "/arenas/sliders.ron");
+        assert!(parse(&["--arena", small, "--mode", "2p"]).is_ok());
+        assert!(parse(&["--arena", small, "--mode", "bricks"]).is_err());
+    }
+
+    #[test]
+    fn flags_override_the_config_file() {
+        let config = concat!(env!("CARGO_MANIFEST_DIR"), "/pong.ron");
+        let options =
+            parse(&["--power-ups", "--spin", "--balls", "3", "--config", config]).unwrap();
+        assert!(options.config.power_ups.enabled);
+        assert!(options.config.spin.enabled);
+        assert_eq!(options.config.multiball.max_balls, 3);
+    }
+}
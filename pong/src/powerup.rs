@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::collision::Rect;
+use crate::game::Player;
+
+// How far a pickup reaches from its centre, in the same units as the court
+pub const POWER_UP_HALF_SIZE: f32 = 6.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerUpKind {
+    GrowPaddle,
+    ShrinkPaddle,
+    FastBall,
+    SlowBall,
+    InvertControls,
+    SplitBall,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::GrowPaddle,
+        PowerUpKind::ShrinkPaddle,
+        PowerUpKind::FastBall,
+        PowerUpKind::SlowBall,
+        PowerUpKind::InvertControls,
+        PowerUpKind::SplitBall,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUpKind::GrowPaddle => "Big Paddle",
+            PowerUpKind::ShrinkPaddle => "Small Paddle",
+            PowerUpKind::FastBall => "Fast Ball",
+            PowerUpKind::SlowBall => "Slow Ball",
+            PowerUpKind::InvertControls => "Backwards Controls",
+            PowerUpKind::SplitBall => "Split Ball",
+        }
+    }
+
+    /// Whether an effect lands on everyone but the player who picked it up, rather than on them
+    pub fn against_opponents(self) -> bool {
+        matches!(
+            self,
+            PowerUpKind::ShrinkPaddle | PowerUpKind::InvertControls
+        )
+    }
+}
+
+/// A pickup on the court waiting for a ball to pass through it, or a paddle to catch it
+#[derive(Clone, Copy, Debug)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    pub position: [f32; 2],
+    // Distance moved per step, pickups dropped by bricks fall towards the paddle
+    pub velocity: [f32; 2],
+}
+
+impl PowerUp {
+    pub fn rect(&self) -> Rect {
+        Rect {
+            min: [
+                self.position[0] - POWER_UP_HALF_SIZE,
+                self.position[1] - POWER_UP_HALF_SIZE,
+            ],
+            max: [
+                self.position[0] + POWER_UP_HALF_SIZE,
+                self.position[1] + POWER_UP_HALF_SIZE,
+            ],
+        }
+    }
+}
+
+/// A power-up that has been picked up and is still working
+#[derive(Clone, Copy, Debug)]
+pub struct Effect {
+    pub kind: PowerUpKind,
+    // Who it's working on, or for
+    pub player: Player,
+    pub ticks_left: u32,
+}
+
+/// All the effects working at the moment, and what they add up to
+#[derive(Clone, Debug, Default)]
+pub struct Effects {
+    active: Vec<Effect>,
+}
+
+impl Effects {
+    /// Start an effect, picking the same one up again starts its time over
+    pub fn add(&mut self, kind: PowerUpKind, player: Player, ticks: u32) {
+        self.active
+            .retain(|effect| effect.kind != kind || effect.player != player);
+        self.active.push(Effect {
+            kind,
+            player,
+            ticks_left: ticks,
+        });
+    }
+
+    pub fn tick(&mut self) {
+        for effect in self.active.iter_mut() {
+            effect.ticks_left = effect.ticks_left.saturating_sub(1);
+        }
+        self.active.retain(|effect| effect.ticks_left > 0);
+    }
+
+    fn has(&self, kind: PowerUpKind, player: Player) -> bool {
+        self.active
+            .iter()
+            .any(|effect| effect.kind == kind && effect.player == player)
+    }
+
+    /// How many times its normal height a player's paddle is
+    pub fn paddle_scale(&self, player: Player) -> f32 {
+        let mut scale = 1.0;
+        if self.has(PowerUpKind::GrowPaddle, player) {
+            scale *= 1.5;
+        }
+        if self.has(PowerUpKind::ShrinkPaddle, player) {
+            scale *= 0.6;
+        }
+        scale
+    }
+
+    /// How many times as far every ball moves each step, whoever picked it up
+    pub fn ball_speed_scale(&self) -> f32 {
+        self.active
+            .iter()
+            .map(|effect| match effect.kind {
+                PowerUpKind::FastBall => 1.5,
+                PowerUpKind::SlowBall => 0.6,
+                _ => 1.0,
+            })
+            .product()
+    }
+
+    pub fn controls_inverted(&self, player: Player) -> bool {
+        self.has(PowerUpKind::InvertControls, player)
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+
+// Where records are kept, next to wherever the game is run from
+pub const RECORDS_FILE: &str = "pong_records.ron";
+
+/// Personal bests kept between runs of the game
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Records {
+    // Most returns in a row in practice
+    pub best_streak: u32,
+}
+
+impl Records {
+    /// Read the records saved last time, starting afresh if there aren't any
+    pub fn load(path: &Path) -> Result<Records, String> {
+        if !path.exists() {
+            return Ok(Records::default());
+        }
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        ron::de::from_str(&text).map_err(|err| format!("bad records {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string(self).map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+    }
+
+    /// Keep a practice streak if it's the best yet, returning whether it was
+    pub fn record_streak(&mut self, streak: u32) -> bool {
+        if streak > self.best_streak {
+            self.best_streak = streak;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::GameConfig;
+use crate::game::{Inputs, Layout};
+
+// Bump whenever a change to the simulation would make old replays play out differently
+const REPLAY_VERSION: u32 = 1;
+
+/// Everything needed to play a match out again exactly as it happened
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub layout: Layout,
+    pub config: GameConfig,
+    // Number of steps the match ran for
+    pub ticks: u32,
+    // Only the steps where somebody pressed something, with the step they happened on
+    pub inputs: Vec<(u32, Inputs)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, layout: Layout, config: GameConfig) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            seed,
+            layout,
+            config,
+            ticks: 0,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        let replay: Replay = ron::de::from_str(&text)
+            .map_err(|err| format!("bad replay {}: {}", path.display(), err))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "{} is a version {} replay, only version {} can be played",
+                path.display(),
+                replay.version,
+                REPLAY_VERSION
+            ));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string(self).map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+    }
+}
+
+/// Keeps track of the inputs of a match being played so they can be saved as a replay
+pub struct Recorder {
+    path: PathBuf,
+    replay: Replay,
+}
+
+impl Recorder {
+    pub fn new(path: PathBuf, seed: u64, layout: Layout, config: GameConfig) -> Recorder {
+        Recorder {
+            path,
+            replay: Replay::new(seed, layout, config),
+        }
+    }
+
+    /// Call with the inputs of every step, in order
+    pub fn record(&mut self, inputs: &Inputs) {
+        if !inputs.is_empty() {
+            self.replay.inputs.push((self.replay.ticks, inputs.clone()));
+        }
+        self.replay.ticks += 1;
+    }
+
+    pub fn save(self) -> Result<(), String> {
+        self.replay.save(&self.path)
+    }
+}
+
+/// Hands back a replay's inputs one step at a time
+pub struct Playback {
+    replay: Replay,
+    tick: u32,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback {
+            replay,
+            tick: 0,
+            next: 0,
+        }
+    }
+
+    /// The inputs for the next step, or None once the replay is over
+    pub fn next_inputs(&mut self) -> Option<Inputs> {
+        if self.tick >= self.replay.ticks {
+            return None;
+        }
+        let mut inputs = Inputs::default();
+        if let Some((tick, recorded)) = self.replay.inputs.get(self.next) {
+            if *tick == self.tick {
+                inputs = recorded.clone();
+                self.next += 1;
+            }
+        }
+        self.tick += 1;
+        Some(inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{CpuPlayer, Difficulty};
+    use crate::game::{GameState, Player};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn playback_matches_the_recorded_game() {
+        let path = std::env::temp_dir().join("pong_replay_test.ron");
+        let config = GameConfig::default();
+        let mut game = GameState::new(config.clone(), Layout::Singles, 7);
+        let mut recorder = Recorder::new(path.clone(), 7, Layout::Singles, config);
+        let mut cpu_rng = StdRng::seed_from_u64(3);
+        let mut cpus = [
+            CpuPlayer::new(Player::One, Difficulty::Easy),
+            CpuPlayer::new(Player::Two, Difficulty::Hard),
+        ];
+        let mut recorded_events = Vec::new();
+        for _ in 0..6000 {
+            let mut inputs = Inputs::default();
+            for cpu in cpus.iter_mut() {
+                *inputs.player_mut(cpu.player()) = cpu.think(&game, &mut cpu_rng);
+            }
+            recorder.record(&inputs);
+            recorded_events.push(format!("{:?}", game.step(&inputs)));
+        }
+        recorder.save().unwrap();
+
+        let replay = Replay::load(&path).unwrap();
+        let mut replayed = GameState::new(replay.config.clone(), replay.layout, replay.seed);
+        let mut playback = Playback::new(replay);
+        let mut replayed_events = Vec::new();
+        while let Some(inputs) = playback.next_inputs() {
+            replayed_events.push(format!("{:?}", replayed.step(&inputs)));
+        }
+        assert_eq!(replayed_events, recorded_events);
+        assert!(recorded_events
+            .iter()
+            .any(|events| events.contains("PointScored")));
+        assert_eq!(replayed.score.points, game.score.points);
+        let positions =
+            |game: &GameState| -> Vec<_> { game.balls.iter().map(|ball| ball.position).collect() };
+        assert_eq!(positions(&replayed), positions(&game));
+        let _ = std::fs::remove_file(path);
+    }
+}
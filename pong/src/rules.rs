@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Player, MAX_PLAYERS};
+
+/// Who the ball gets served towards after a point
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ServeTo {
+    Loser,
+    Winner,
+    Alternate,
+    Random,
+}
+
+impl ServeTo {
+    pub const ALL: [ServeTo; 4] = [
+        ServeTo::Loser,
+        ServeTo::Winner,
+        ServeTo::Alternate,
+        ServeTo::Random,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ServeTo::Loser => "loser",
+            ServeTo::Winner => "winner",
+            ServeTo::Alternate => "alternate",
+            ServeTo::Random => "random",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<ServeTo> {
+        ServeTo::ALL
+            .iter()
+            .cloned()
+            .find(|serve_to| serve_to.name() == name)
+    }
+}
+
+/// How a match is won
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchRules {
+    // Points needed to take a set
+    pub points_to_win: u32,
+    // How far ahead the winner of a set has to be
+    pub win_by: u32,
+    // The match is best of this many sets
+    pub sets: u32,
+    // Seconds to show the winner for before the next match starts
+    pub post_match_delay: f32,
+    // Who gets served to after each point
+    pub serve_to: ServeTo,
+    // Seconds the ball waits in the middle before it is served
+    pub serve_delay: f32,
+    // With more than two players, how many balls each can let past before they're out
+    pub lives: u32,
+    // Seconds a match lasts before whoever is ahead wins, going to sudden death if it's level,
+    // 0 to play to points instead
+    pub time_limit: f32,
+}
+
+impl Default for MatchRules {
+    fn default() -> MatchRules {
+        MatchRules {
+            points_to_win: 9,
+            win_by: 1,
+            sets: 1,
+            post_match_delay: 3.0,
+            serve_to: ServeTo::Loser,
+            serve_delay: 3.0,
+            lives: 5,
+            time_limit: 0.0,
+        }
+    }
+}
+
+impl MatchRules {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.points_to_win == 0 {
+            return Err("points to win must be at least 1".to_string());
+        }
+        if self.win_by == 0 {
+            return Err("win by must be at least 1".to_string());
+        }
+        if self.sets == 0 {
+            return Err("there must be at least 1 set".to_string());
+        }
+        if self.post_match_delay < 0.0 {
+            return Err("post match delay can't be negative".to_string());
+        }
+        if self.serve_delay < 0.0 {
+            return Err("serve delay can't be negative".to_string());
+        }
+        if self.lives == 0 {
+            return Err("there must be at least 1 life".to_string());
+        }
+        if self.time_limit < 0.0 {
+            return Err("time limit can't be negative".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn sets_to_win(&self) -> u32 {
+        self.sets / 2 + 1
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.time_limit > 0.0
+    }
+}
+
+/// What a point meant for the match
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Point,
+    SetWon,
+    // A player ran out of lives
+    Eliminated,
+    MatchWon,
+    // Playing on your own, and out of lives
+    MatchLost,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MatchScore {
+    // Points in the current set
+    pub points: [u32; MAX_PLAYERS],
+    pub sets: [u32; MAX_PLAYERS],
+    // Balls let past, when playing for lives
+    pub conceded: [u32; MAX_PLAYERS],
+    // Every point of the match so far, across all sets
+    pub points_played: u32,
+    // Returns in a row, when practicing on your own
+    pub streak: u32,
+}
+
+impl MatchScore {
+    pub fn points(&self, player: Player) -> u32 {
+        self.points[player.index()]
+    }
+
+    pub fn sets(&self, player: Player) -> u32 {
+        self.sets[player.index()]
+    }
+
+    pub fn award_point(&mut self, player: Player, rules: &MatchRules) -> Outcome {
+        self.points_played += 1;
+        self.points[player.index()] += 1;
+        let points = self.points(player);
+        let opponent = self.points(player.other());
+        // Timed matches are won on the clock, not by reaching a score
+        if rules.is_timed() {
+            return Outcome::Point;
+        }
+        if points < rules.points_to_win || points < opponent + rules.win_by {
+            return Outcome::Point;
+        }
+        self.sets[player.index()] += 1;
+        if self.sets(player) == rules.sets_to_win() {
+            // Leave the final score up for the end of the match
+            return Outcome::MatchWon;
+        }
+        self.points = [0; MAX_PLAYERS];
+        Outcome::SetWon
+    }
+
+    pub fn lives_left(&self, player: Player, rules: &MatchRules) -> u32 {
+        rules.lives.saturating_sub(self.conceded[player.index()])
+    }
+
+    pub fn lose_life(&mut self, player: Player, rules: &MatchRules) -> Outcome {
+        self.points_played += 1;
+        self.conceded[player.index()] += 1;
+        if self.lives_left(player, rules) == 0 {
+            Outcome::Eliminated
+        } else {
+            Outcome::Point
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Play out points in order, returning what the last one meant
+    fn play(score: &mut MatchScore, rules: &MatchRules, points: &[Player]) -> Outcome {
+        let mut outcome = Outcome::Point;
+        for &player in points {
+            outcome = score.award_point(player, rules);
+        }
+        outcome
+    }
+
+    #[test]
+    fn set_needs_the_winning_margin() {
+        let rules = MatchRules {
+            points_to_win: 3,
+            win_by: 2,
+            ..MatchRules::default()
+        };
+        let mut score = MatchScore::default();
+        let (one, two) = (Player::One, Player::Two);
+        assert_eq!(
+            play(&mut score, &rules, &[one, two, one, two]),
+            Outcome::Point
+        );
+        // 3-2 isn't far enough ahead
+        assert_eq!(play(&mut score, &rules, &[one]), Outcome::Point);
+        assert_eq!(play(&mut score, &rules, &[two, two]), Outcome::Point);
+        assert_eq!(play(&mut score, &rules, &[two]), Outcome::MatchWon);
+        assert_eq!(score.points(two), 5);
+        assert_eq!(score.sets(two), 1);
+    }
+
+    #[test]
+    fn best_of_three_sets() {
+        let rules = MatchRules {
+            points_to_win: 2,
+            sets: 3,
+            ..MatchRules::default()
+        };
+        let mut score = MatchScore::default();
+        let (one, two) = (Player::One, Player::Two);
+        assert_eq!(play(&mut score, &rules, &[one, one]), Outcome::SetWon);
+        // Points start again for the next set
+        assert_eq!(score.points(one), 0);
+        assert_eq!(play(&mut score, &rules, &[two, two]), Outcome::SetWon);
+        assert_eq!(
+            play(&mut score, &rules, &[one, two, one]),
+            Outcome::MatchWon
+        );
+        assert_eq!((score.sets(one), score.sets(two)), (2, 1));
+        assert_eq!(score.points_played, 7);
+    }
+
+    #[test]
+    fn timed_matches_keep_scoring_past_the_target() {
+        let rules = MatchRules {
+            points_to_win: 1,
+            time_limit: 60.0,
+            ..MatchRules::default()
+        };
+        let mut score = MatchScore::default();
+        let points = [Player::One; 5];
+        assert_eq!(play(&mut score, &rules, &points), Outcome::Point);
+        assert_eq!(score.points(Player::One), 5);
+        assert_eq!(score.sets(Player::One), 0);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::game::Player;
+use crate::timestep::TICKS_PER_SECOND;
+
+/// Which part of the game is showing, this decides what gets simulated and drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Screen {
+    Title,
+    Playing,
+    Paused,
+    // Steps left to show the winner for before going back to the title, nobody wins when playing
+    // on your own runs out of lives
+    GameOver {
+        winner: Option<Player>,
+        ticks_left: u32,
+    },
+}
+
+impl Screen {
+    pub fn game_over(winner: Option<Player>, delay: f32) -> Screen {
+        Screen::GameOver {
+            winner,
+            ticks_left: (delay * TICKS_PER_SECOND as f32) as u32,
+        }
+    }
+
+    pub fn toggle_pause(self) -> Screen {
+        match self {
+            Screen::Playing => Screen::Paused,
+            Screen::Paused => Screen::Playing,
+            screen => screen,
+        }
+    }
+
+    /// Count down anything timed on this screen by one step
+    pub fn tick(self) -> Screen {
+        match self {
+            Screen::GameOver { ticks_left: 0, .. } => Screen::Title,
+            Screen::GameOver { winner, ticks_left } => Screen::GameOver {
+                winner,
+                ticks_left: ticks_left - 1,
+            },
+            screen => screen,
+        }
+    }
+}
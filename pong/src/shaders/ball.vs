@@ -4,11 +4,19 @@ layout(location = 1) in vec3 color;
 layout(location = 0) out vec3 fragColor;
 
 layout(push_constant) uniform BallPosition {
+    // See Camera::projection
+    vec4 projection;
     vec2 vector;
+    float angle;
 } disp;
 
 
 void main() {
-    gl_Position = vec4(position+disp.vector, 0.0, 1.0);
+    // Turn the ball to show how it's spinning
+    float c = cos(disp.angle);
+    float s = sin(disp.angle);
+    vec2 turned = vec2(c * position.x - s * position.y, s * position.x + c * position.y);
+    vec2 world = turned+disp.vector;
+    gl_Position = vec4(world*disp.projection.xy+disp.projection.zw, 0.0, 1.0);
     fragColor = color;
 } 
\ No newline at end of file
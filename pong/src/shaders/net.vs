@@ -3,7 +3,12 @@ layout(location = 0) in vec2 position;
 layout(location = 1) in vec3 color;
 layout(location = 0) out vec3 fragColor;
 
+layout(push_constant) uniform Projection {
+    // See Camera::projection
+    vec4 projection;
+} view;
+
 void main() {
-    gl_Position = vec4(position.x,position.y, 0.0, 1.0);
+    gl_Position = vec4(position*view.projection.xy+view.projection.zw, 0.0, 1.0);
     fragColor = color;
 } 
\ No newline at end of file
@@ -0,0 +1,16 @@
+#version 450
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec3 color;
+layout(location = 0) out vec3 fragColor;
+
+layout(push_constant) uniform PaddleOffset {
+    // See Camera::projection
+    vec4 projection;
+    vec2 offset;
+} disp;
+
+void main() {
+    vec2 world = position+disp.offset;
+    gl_Position = vec4(world*disp.projection.xy+disp.projection.zw, 0.0, 1.0);
+    fragColor = color;
+}
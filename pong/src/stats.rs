@@ -0,0 +1,173 @@
+use serde::Serialize;
+
+use std::fs;
+use std::path::Path;
+
+use crate::game::{GameEvent, Layout, Player};
+use crate::timestep::TICKS_PER_SECOND;
+
+/// How a match went, kept up to date as it's played
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchStats {
+    // Rallies played to a point, a life lost or the end of a streak
+    pub rallies: u32,
+    // Most paddle hits in one rally
+    pub longest_rally: u32,
+    // Seconds from the serve to the ball getting past someone, on average
+    pub average_rally_seconds: f32,
+    // Everything below is per player, in order
+    pub paddle_hits: Vec<u32>,
+    // Points won with the ball first served towards the other end
+    pub points_on_serve: Vec<u32>,
+    // Points won with the ball first served towards the scorer's end
+    pub points_on_return: Vec<u32>,
+    #[serde(skip)]
+    layout: Layout,
+    // Steps every finished rally took, all together
+    #[serde(skip)]
+    rally_ticks: u32,
+    // Hits and steps so far in the rally being played, if there is one
+    #[serde(skip)]
+    rally: Option<(u32, u32)>,
+    #[serde(skip)]
+    receiver: Option<Player>,
+}
+
+impl MatchStats {
+    pub fn new(layout: Layout) -> MatchStats {
+        let players = layout.players();
+        MatchStats {
+            rallies: 0,
+            longest_rally: 0,
+            average_rally_seconds: 0.0,
+            paddle_hits: vec![0; players],
+            points_on_serve: vec![0; players],
+            points_on_return: vec![0; players],
+            layout,
+            rally_ticks: 0,
+            rally: None,
+            receiver: None,
+        }
+    }
+
+    /// Take in what happened in a step of the match
+    pub fn record(&mut self, events: &[GameEvent]) {
+        if let Some((_, ticks)) = self.rally.as_mut() {
+            *ticks += 1;
+        }
+        for event in events {
+            match *event {
+                GameEvent::Served(receiver) => {
+                    self.rally = Some((0, 0));
+                    self.receiver = Some(receiver);
+                }
+                GameEvent::PaddleHit(player) => {
+                    self.paddle_hits[player.index()] += 1;
+                    if let Some((hits, _)) = self.rally.as_mut() {
+                        *hits += 1;
+                    }
+                }
+                GameEvent::PointScored(scorer) => {
+                    self.end_rally();
+                    // Only the two ends score points, so the end the ball went to says who served
+                    if let Some(receiver) = self.receiver {
+                        if self.layout.edge(scorer) == self.layout.edge(receiver) {
+                            self.points_on_return[scorer.index()] += 1;
+                        } else {
+                            self.points_on_serve[scorer.index()] += 1;
+                        }
+                    }
+                }
+                GameEvent::LifeLost | GameEvent::StreakEnded(_) => self.end_rally(),
+                _ => (),
+            }
+        }
+    }
+
+    // The ball got past someone. With more than one ball in play the rest carry on as a new rally.
+    fn end_rally(&mut self) {
+        let (hits, ticks) = match self.rally.take() {
+            Some(rally) => rally,
+            None => return,
+        };
+        self.rallies += 1;
+        self.longest_rally = self.longest_rally.max(hits);
+        self.rally_ticks += ticks;
+        self.average_rally_seconds =
+            self.rally_ticks as f32 / self.rallies as f32 / TICKS_PER_SECOND as f32;
+        self.rally = Some((0, 0));
+    }
+
+    /// Save the stats as JSON
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_steps(stats: &mut MatchStats, steps: &[&[GameEvent]]) {
+        for events in steps {
+            stats.record(events);
+        }
+    }
+
+    #[test]
+    fn points_count_towards_serve_or_return() {
+        let (one, two) = (Player::One, Player::Two);
+        let mut stats = MatchStats::new(Layout::Singles);
+        stats.record(&[GameEvent::Served(two)]);
+        for _ in 0..85 {
+            stats.record(&[]);
+        }
+        record_steps(
+            &mut stats,
+            &[
+                &[GameEvent::PaddleHit(two)],
+                &[GameEvent::PaddleHit(one)],
+                &[GameEvent::PaddleHit(two)],
+                // Served towards Two, so One scored off their own serve
+                &[GameEvent::PointScored(one)],
+                &[GameEvent::Served(two)],
+                &[GameEvent::PointScored(two)],
+            ],
+        );
+        assert_eq!(stats.points_on_serve, vec![1, 0]);
+        assert_eq!(stats.points_on_return, vec![0, 1]);
+        assert_eq!(stats.paddle_hits, vec![1, 2]);
+        assert_eq!(stats.rallies, 2);
+        assert_eq!(stats.longest_rally, 3);
+        // 89 steps then 1, for 90 steps over two rallies
+        assert!((stats.average_rally_seconds - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn balls_left_in_play_carry_on_as_a_new_rally() {
+        let (one, two) = (Player::One, Player::Two);
+        let mut stats = MatchStats::new(Layout::Singles);
+        record_steps(
+            &mut stats,
+            &[
+                &[GameEvent::Served(two)],
+                &[GameEvent::PaddleHit(two)],
+                &[GameEvent::BallAdded],
+                &[GameEvent::PaddleHit(one), GameEvent::PaddleHit(two)],
+                &[GameEvent::PointScored(two)],
+                &[GameEvent::PaddleHit(one)],
+                &[GameEvent::PointScored(one)],
+            ],
+        );
+        assert_eq!(stats.rallies, 2);
+        assert_eq!(stats.longest_rally, 3);
+        assert_eq!(stats.paddle_hits, vec![2, 2]);
+        // Nobody served again, so the ball still counts as served towards Two
+        assert_eq!(stats.points_on_serve, vec![1, 0]);
+        assert_eq!(stats.points_on_return, vec![0, 1]);
+        // 4 steps then 2
+        let seconds = 6.0 / 2.0 / TICKS_PER_SECOND as f32;
+        assert!((stats.average_rally_seconds - seconds).abs() < 1e-6);
+    }
+}
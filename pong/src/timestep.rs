@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+// The game was tuned for one step per frame on a 60 Hz display
+pub const TICKS_PER_SECOND: u32 = 60;
+// Don't try to catch up on more than this after a stall, e.g. dragging the window
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+/// Turns real time between frames into a whole number of simulation ticks
+pub struct FixedTimestep {
+    tick: Duration,
+    accumulator: Duration,
+    last: Instant,
+}
+
+impl FixedTimestep {
+    pub fn new(ticks_per_second: u32) -> FixedTimestep {
+        FixedTimestep {
+            tick: Duration::from_secs(1) / ticks_per_second,
+            accumulator: Duration::from_secs(0),
+            last: Instant::now(),
+        }
+    }
+
+    /// How many ticks to simulate for the time passed since the last call
+    pub fn ticks(&mut self) -> u32 {
+        let now = Instant::now();
+        let elapsed = now - self.last;
+        self.last = now;
+        self.advance(elapsed)
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed.min(MAX_FRAME_TIME);
+        let mut ticks = 0;
+        while self.accumulator >= self.tick {
+            self.accumulator -= self.tick;
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// How far between the last tick and the next one we are, from 0 to 1
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.tick.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leftover_time_carries_over_to_the_next_frame() {
+        let mut timestep = FixedTimestep::new(100);
+        assert_eq!(timestep.advance(Duration::from_millis(25)), 2);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+        assert_eq!(timestep.advance(Duration::from_millis(5)), 1);
+        assert_eq!(timestep.alpha(), 0.0);
+        assert_eq!(timestep.advance(Duration::from_millis(3)), 0);
+    }
+
+    #[test]
+    fn stalls_only_catch_up_a_quarter_of_a_second() {
+        let mut timestep = FixedTimestep::new(100);
+        assert_eq!(timestep.advance(Duration::from_secs(5)), 25);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+}